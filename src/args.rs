@@ -22,58 +22,352 @@
 * SOFTWARE.
 *
 * File created: 2023-10-02
-* Last updated: 2023-10-04
+* Last updated: 2026-10-18
 */
 
 use std::path::PathBuf;
+use std::str::FromStr;
 
-use clap::Parser;
+use clap::{Args as ClapArgs, Parser, Subcommand};
+use docstring_rs::{Style, DEBOUNCE};
 use text_io::read;
 
-///
+/// Command line arguments of docstring-rs.
 #[derive(Parser, Debug)]
-#[command(author, version, about)]
+#[command(author, version, about, arg_required_else_help = true)]
 pub struct Args {
-    /// Name of the directory in which to create new file or update existing files,
-    /// if it does not already exist, creates the directory.
-    #[arg(short = 'd', long = "directory", required = true)]
+    /// Prompt for the arguments that were not given instead of using their defaults.
+    /// Without a subcommand, prompts for everything needed to create or update a file.
+    #[arg(short = 'i', long = "interactive", global = true)]
+    pub interactive: bool,
+
+    /// Path to the config file to use. If not specified, uses `.docstring.toml` at
+    /// the top of the git repository, or in the current working directory outside
+    /// of one, if it exists.
+    #[arg(long = "config", value_name = "PATH", global = true)]
+    pub config: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// The operations that docstring-rs can perform.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Create a new file with a docstring as header, or add it to the file if it
+    /// already exists.
+    Add(AddArgs),
+
+    /// Add or update the docstrings of all files in a directory recursively.
+    Update(UpdateArgs),
+
+    /// Check that all files in a directory have up to date docstrings, exits with
+    /// a non-zero status if any do not.
+    Check(UpdateArgs),
+
+    /// Remove the docstrings generated by docstring-rs from all files in a directory
+    /// recursively.
+    Remove(RemoveArgs),
+
+    /// Replace the docstrings declaring one license with docstrings declaring the
+    /// license given by `--license`, keeping their created dates and copyright holders.
+    Relicense(RelicenseArgs),
+
+    /// Summarise the license declared by every file in a directory per license and
+    /// directory, exits with a non-zero status if any file declares a license other
+    /// than the one given by `--license`.
+    Audit(WalkArgs),
+
+    /// Read source code on stdin and write it to stdout with its docstring added or
+    /// updated, without touching any file. Meant for editors and build pipelines.
+    Format(FormatArgs),
+
+    /// Watch a directory and add docstrings to new files as they are created, and to
+    /// files that become empty. Runs until interrupted.
+    Watch(WatchArgs),
+
+    /// Check the docstrings of the files staged in git, or of the given files, exits
+    /// with a non-zero status if any are not up to date. Meant to run before every
+    /// commit, see `install-hook`.
+    PreCommit(PreCommitArgs),
+
+    /// Install a git pre-commit hook in the current repository that runs `pre-commit`.
+    InstallHook(InstallHookArgs),
+
+    /// List all supported languages, including those declared in the config file, with
+    /// their comment style, file endings and file names.
+    ListLanguages,
+}
+
+/// Arguments for creating or updating a single file.
+#[derive(ClapArgs, Debug)]
+pub struct AddArgs {
+    /// Name of the directory in which to create the new file, if it does not
+    /// already exist, creates the directory.
+    #[arg(short = 'd', long = "directory")]
     pub directory: String,
 
     /// Name of a new file to create with docstring as header. Prepends to top of file
     /// if the target file already exists.
-    #[arg(short = 'f', long = "file", required = false, default_value = "*.*")]
+    #[arg(short = 'f', long = "file")]
     pub file_name: String,
 
     /// Relative path to the LICENSE file to use as header docstring. If not specified,
-    /// expects a LICENSE file to exist in the current working directory.
+    /// uses the `license` of the config file, or expects a LICENSE file to exist in
+    /// the current working directory.
+    #[arg(short = 'l', long = "license")]
+    pub license: Option<String>,
+
+    #[command(flatten)]
+    pub guard: GuardArgs,
+
+    #[command(flatten)]
+    pub style: StyleArgs,
+}
+
+/// Arguments protecting docstrings that declare another license.
+#[derive(ClapArgs, Debug, Default)]
+pub struct GuardArgs {
+    /// Replace existing docstrings even if they declare another license than the
+    /// one given by `--license`.
+    #[arg(long = "force")]
+    pub force: bool,
+
+    /// Replace existing docstrings declaring another license in files matching this
+    /// glob, e.g. `third_party/**`. Can be given multiple times.
+    #[arg(long = "allow", value_name = "GLOB")]
+    pub allow: Vec<String>,
+}
+
+/// Arguments choosing the comment style docstrings are written in.
+#[derive(ClapArgs, Debug, Default)]
+pub struct StyleArgs {
+    /// Write docstrings as one `block` comment or as a `line` comment on every line,
+    /// optionally for one language only, e.g. `rust=line`. Can be given multiple
+    /// times. If not specified, uses the default style of each language.
+    #[arg(long = "style", value_name = "[LANG=]STYLE")]
+    pub style: Vec<StyleChoice>,
+}
+
+/// A comment style chosen for all languages, or for one language only. The
+/// language is looked up once the config file, which may declare it, is loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleChoice {
+    pub lang: Option<String>,
+    pub style: Style,
+}
+
+impl FromStr for StyleChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((lang, style)) => Ok(Self {
+                lang: Some(lang.to_owned()),
+                style: style.parse()?,
+            }),
+            None => Ok(Self {
+                lang: None,
+                style: s.parse()?,
+            }),
+        }
+    }
+}
+
+/// Arguments for operations on all files in a directory, or on a list of files.
+#[derive(ClapArgs, Debug)]
+pub struct WalkArgs {
+    /// Files or directories to process, directories are searched recursively.
+    #[arg(value_name = "PATH")]
+    pub paths: Vec<String>,
+
+    /// Name of the directory in which to recursively look for files.
     #[arg(
-        short = 'l',
-        long = "license",
-        required = false,
-        default_value = "LICENSE"
+        short = 'd',
+        long = "directory",
+        required_unless_present_any = ["paths", "files_from"]
     )]
-    pub license: String,
+    pub directory: Option<String>,
+
+    /// Also process the paths listed in this file, or on stdin if `-`, one per line
+    /// or separated by NUL bytes, e.g. from `git ls-files -z`.
+    #[arg(long = "files-from", value_name = "FILE")]
+    pub files_from: Option<String>,
+
+    /// Only process files of this language, given as its name or one of its file
+    /// endings. If not specified, processes files of all supported languages.
+    #[arg(long = "lang")]
+    pub lang: Option<String>,
 
-    /// Specify whether or not to try and update all available docstrings in a directory
-    /// recursively, requires <DIRECTORY> to have been set.
+    /// Only process files that changed between this git revision, e.g. `main`, and
+    /// the working tree, whether their changes are staged or not.
+    #[arg(long = "since", value_name = "REV")]
+    pub since: Option<String>,
+
+    /// With `--since`, also process files that are not tracked by git and not
+    /// ignored by it.
+    #[arg(long = "untracked", requires = "since")]
+    pub untracked: bool,
+
+    /// Skip files whose path, relative to the directory, matches this glob. Can be
+    /// given multiple times.
+    #[arg(long = "ignore", value_name = "GLOB")]
+    pub ignore: Vec<String>,
+
+    /// Relative path to the LICENSE file to use as header docstring. If not specified,
+    /// uses the `license` of the config file, or expects a LICENSE file to exist in
+    /// the current working directory.
+    #[arg(short = 'l', long = "license")]
+    pub license: Option<String>,
+}
+
+/// Arguments for adding, updating or checking docstrings of all files in a directory.
+#[derive(ClapArgs, Debug)]
+pub struct UpdateArgs {
+    #[command(flatten)]
+    pub walk: WalkArgs,
+
+    #[command(flatten)]
+    pub guard: GuardArgs,
+
+    #[command(flatten)]
+    pub style: StyleArgs,
+}
+
+/// Arguments for adding or updating the docstring of source code on stdin.
+#[derive(ClapArgs, Debug)]
+pub struct FormatArgs {
+    /// Name of the file the source code on stdin belongs to, which picks its language
+    /// and is matched against the config file. The file is never read or written.
     #[arg(
-        short = 'u',
-        long = "update",
-        required = false,
-        requires = "directory",
-        default_value = "false"
+        long = "stdin-filename",
+        value_name = "PATH",
+        required_unless_present = "lang"
     )]
-    pub update: bool,
+    pub stdin_filename: Option<String>,
+
+    /// Language of the source code on stdin, given as its name or one of its file
+    /// endings. Takes precedence over `--stdin-filename`.
+    #[arg(long = "lang")]
+    pub lang: Option<String>,
+
+    /// Relative path to the LICENSE file to use as header docstring. If not specified,
+    /// uses the `license` of the config file, or expects a LICENSE file to exist in
+    /// the current working directory.
+    #[arg(short = 'l', long = "license")]
+    pub license: Option<String>,
+
+    #[command(flatten)]
+    pub guard: GuardArgs,
+
+    #[command(flatten)]
+    pub style: StyleArgs,
 }
 
-///
-impl Args {
-    pub fn get_filetype_from_user(&mut self) {
-        print!("Please input the file type to update: ");
-        let f: String = read!();
-        self.file_name = f;
-    }
-    ///
+/// Arguments for watching a directory for new files.
+#[derive(ClapArgs, Debug)]
+pub struct WatchArgs {
+    #[command(flatten)]
+    pub walk: WalkArgs,
+
+    #[command(flatten)]
+    pub guard: GuardArgs,
+
+    #[command(flatten)]
+    pub style: StyleArgs,
+
+    /// How many milliseconds a file has to be left alone after a change before it is
+    /// given a docstring, so that editors can finish writing it.
+    #[arg(long = "debounce", value_name = "MS", default_value_t = DEBOUNCE.as_millis() as u64)]
+    pub debounce: u64,
+}
+
+/// Arguments for checking or fixing the docstrings of the files in a commit.
+#[derive(ClapArgs, Debug)]
+pub struct PreCommitArgs {
+    /// Files to process, as passed by pre-commit frameworks. If not specified,
+    /// processes the files staged in the git index.
+    #[arg(value_name = "PATH")]
+    pub paths: Vec<String>,
+
+    /// Add or update the docstrings instead of only checking them. Exits with a
+    /// non-zero status if any file was changed, unless `--stage` is given.
+    #[arg(long = "fix")]
+    pub fix: bool,
+
+    /// Stage the files whose docstrings were fixed, except for files with other
+    /// unstaged changes, which would otherwise end up in the commit.
+    #[arg(long = "stage", requires = "fix")]
+    pub stage: bool,
+
+    /// Skip files matching this glob. Can be given multiple times.
+    #[arg(long = "ignore", value_name = "GLOB")]
+    pub ignore: Vec<String>,
+
+    /// Relative path to the LICENSE file to use as header docstring. If not specified,
+    /// uses the `license` of the config file, or expects a LICENSE file to exist in
+    /// the current working directory.
+    #[arg(short = 'l', long = "license")]
+    pub license: Option<String>,
+
+    #[command(flatten)]
+    pub guard: GuardArgs,
+
+    #[command(flatten)]
+    pub style: StyleArgs,
+}
+
+/// Arguments for installing the git pre-commit hook.
+#[derive(ClapArgs, Debug)]
+pub struct InstallHookArgs {
+    /// Make the hook fix docstrings instead of only checking them.
+    #[arg(long = "fix")]
+    pub fix: bool,
+
+    /// Make the hook stage the files whose docstrings it fixed.
+    #[arg(long = "stage", requires = "fix")]
+    pub stage: bool,
+
+    /// Relative path, from the top of the repository, to the LICENSE file the hook
+    /// uses as header docstring. If not specified, the hook uses the `license` of the
+    /// config file, or the LICENSE file at the top of the repository.
+    #[arg(short = 'l', long = "license")]
+    pub license: Option<String>,
+
+    /// Replace an existing pre-commit hook that was not installed by docstring-rs.
+    #[arg(long = "overwrite")]
+    pub overwrite: bool,
+}
+
+/// Arguments for removing docstrings.
+#[derive(ClapArgs, Debug)]
+pub struct RemoveArgs {
+    #[command(flatten)]
+    pub walk: WalkArgs,
+
+    /// Only print which files would have their docstring removed.
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+/// Arguments for relicensing docstrings.
+#[derive(ClapArgs, Debug)]
+pub struct RelicenseArgs {
+    #[command(flatten)]
+    pub walk: WalkArgs,
+
+    /// The license to replace, given as a path to its LICENSE file or as an SPDX id,
+    /// e.g. `MIT`.
+    #[arg(long = "from", value_name = "LICENSE")]
+    pub from: String,
+
+    /// Only print which files would be relicensed.
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+impl AddArgs {
+    /// Prompt the user for all arguments needed to create or update a single file.
     pub fn try_from_user() -> Self {
         print!("Please input the DIRECTORY PATH to create create/update file at: ");
         let d: String = read!();
@@ -87,23 +381,27 @@ impl Args {
         Self {
             directory: d,
             file_name: f,
-            license: l,
-            update: false,
+            license: Some(l),
+            guard: GuardArgs::default(),
+            style: StyleArgs::default(),
         }
     }
 
-    ///
-    pub fn paths(&self) -> (PathBuf, PathBuf, PathBuf) {
+    /// The directory and file name arguments as paths.
+    pub fn paths(&self) -> (PathBuf, PathBuf) {
         (
             PathBuf::from(&self.directory),
             PathBuf::from(&self.file_name),
-            PathBuf::from(&self.license),
         )
     }
+}
 
-    ///
-    pub fn update(&self) -> bool {
-        self.update
+impl WalkArgs {
+    /// Prompt the user for the language to process.
+    pub fn get_lang_from_user(&mut self) {
+        print!("Please input the language or file type to process: ");
+        let l: String = read!();
+        self.lang = Some(l);
     }
 }
 
@@ -119,15 +417,224 @@ mod tests_args {
 
     #[test]
     fn args_into_paths() {
-        let args = Args {
+        let args = AddArgs {
             directory: "src".into(),
             file_name: "nn.rs".into(),
-            license: "LICENSE".into(),
-            update: false,
+            license: Some("LICENSE".into()),
+            guard: GuardArgs::default(),
+            style: StyleArgs::default(),
         };
-        let (d, f, l) = args.paths();
+        let (d, f) = args.paths();
         assert_eq!(PathBuf::from("src"), d);
         assert_eq!(PathBuf::from("nn.rs"), f);
-        assert_eq!(PathBuf::from("LICENSE"), l);
+    }
+
+    #[test]
+    fn parse_subcommands() {
+        let args =
+            Args::try_parse_from(["docstring-rs", "check", "-d", "src", "--lang", "rust"]).unwrap();
+        match args.command {
+            Some(Command::Check(u)) => {
+                assert_eq!(u.walk.directory.as_deref(), Some("src"));
+                assert_eq!(u.walk.lang.as_deref(), Some("rust"));
+                assert_eq!(u.walk.license, None);
+                assert!(!u.guard.force);
+            }
+            c => panic!("expected check, got {:?}", c),
+        };
+
+        let args = Args::try_parse_from(["docstring-rs", "-i", "add", "-d", "src", "-f", "nn.rs"])
+            .unwrap();
+        assert!(args.interactive);
+        assert!(matches!(args.command, Some(Command::Add(_))));
+    }
+
+    #[test]
+    fn parse_remove() {
+        let args = Args::try_parse_from([
+            "docstring-rs",
+            "remove",
+            "-d",
+            "src",
+            "--ignore",
+            "vendor/**",
+            "--ignore",
+            "*.pyi",
+            "--dry-run",
+        ])
+        .unwrap();
+        match args.command {
+            Some(Command::Remove(r)) => {
+                assert!(r.dry_run);
+                assert_eq!(r.walk.ignore, vec!["vendor/**", "*.pyi"]);
+            }
+            c => panic!("expected remove, got {:?}", c),
+        };
+    }
+
+    #[test]
+    fn parse_relicense() {
+        let args = Args::try_parse_from([
+            "docstring-rs",
+            "relicense",
+            "-d",
+            "src",
+            "--from",
+            "MIT",
+            "-l",
+            "Apache-2.0",
+        ])
+        .unwrap();
+        match args.command {
+            Some(Command::Relicense(r)) => {
+                assert_eq!(r.from, "MIT");
+                assert_eq!(r.walk.license.as_deref(), Some("Apache-2.0"));
+                assert!(!r.dry_run);
+            }
+            c => panic!("expected relicense, got {:?}", c),
+        };
+    }
+
+    #[test]
+    fn parse_guard() {
+        let args = Args::try_parse_from([
+            "docstring-rs",
+            "update",
+            "-d",
+            "src",
+            "--allow",
+            "third_party/**",
+        ])
+        .unwrap();
+        match args.command {
+            Some(Command::Update(u)) => {
+                assert!(!u.guard.force);
+                assert_eq!(u.guard.allow, vec!["third_party/**"]);
+            }
+            c => panic!("expected update, got {:?}", c),
+        };
+    }
+
+    #[test]
+    fn parse_style() {
+        let args = Args::try_parse_from([
+            "docstring-rs",
+            "update",
+            "-d",
+            "src",
+            "--style",
+            "line",
+            "--style",
+            "python=block",
+        ])
+        .unwrap();
+        match args.command {
+            Some(Command::Update(u)) => assert_eq!(
+                u.style.style,
+                vec![
+                    StyleChoice {
+                        lang: None,
+                        style: Style::Line
+                    },
+                    StyleChoice {
+                        lang: Some("python".to_owned()),
+                        style: Style::Block
+                    },
+                ]
+            ),
+            c => panic!("expected update, got {:?}", c),
+        };
+
+        assert!(
+            Args::try_parse_from(["docstring-rs", "check", "-d", "src", "--style", "box"]).is_err()
+        );
+        assert!(Args::try_parse_from([
+            "docstring-rs",
+            "check",
+            "-d",
+            "src",
+            "--style",
+            "python=wavy"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn walk_paths() {
+        let args = Args::try_parse_from([
+            "docstring-rs",
+            "update",
+            "src/main.rs",
+            "tests",
+            "--files-from",
+            "-",
+        ])
+        .unwrap();
+        match args.command {
+            Some(Command::Update(u)) => {
+                assert_eq!(u.walk.paths, vec!["src/main.rs", "tests"]);
+                assert_eq!(u.walk.directory, None);
+                assert_eq!(u.walk.files_from.as_deref(), Some("-"));
+            }
+            c => panic!("expected update, got {:?}", c),
+        };
+        assert!(Args::try_parse_from(["docstring-rs", "check", "--lang", "rust"]).is_err());
+    }
+
+    #[test]
+    fn walk_since() {
+        let args = Args::try_parse_from([
+            "docstring-rs",
+            "check",
+            ".",
+            "--since",
+            "main",
+            "--untracked",
+        ])
+        .unwrap();
+        match args.command {
+            Some(Command::Check(u)) => {
+                assert_eq!(u.walk.since.as_deref(), Some("main"));
+                assert!(u.walk.untracked);
+            }
+            c => panic!("expected check, got {:?}", c),
+        };
+        assert!(Args::try_parse_from(["docstring-rs", "check", ".", "--untracked"]).is_err());
+    }
+
+    #[test]
+    fn format_stdin() {
+        let args =
+            Args::try_parse_from(["docstring-rs", "format", "--stdin-filename", "src/foo.rs"])
+                .unwrap();
+        match args.command {
+            Some(Command::Format(f)) => {
+                assert_eq!(f.stdin_filename.as_deref(), Some("src/foo.rs"));
+                assert_eq!(f.lang, None);
+            }
+            c => panic!("expected format, got {:?}", c),
+        };
+        assert!(Args::try_parse_from(["docstring-rs", "format", "--lang", "rust"]).is_ok());
+        assert!(Args::try_parse_from(["docstring-rs", "format"]).is_err());
+    }
+
+    #[test]
+    fn pre_commit_paths() {
+        let args =
+            Args::try_parse_from(["docstring-rs", "pre-commit", "--fix", "a.rs", "b.py"]).unwrap();
+        match args.command {
+            Some(Command::PreCommit(p)) => {
+                assert_eq!(p.paths, vec!["a.rs", "b.py"]);
+                assert!(p.fix && !p.stage);
+            }
+            c => panic!("expected pre-commit, got {:?}", c),
+        };
+        assert!(Args::try_parse_from(["docstring-rs", "pre-commit", "--stage"]).is_err());
+        assert!(Args::try_parse_from(["docstring-rs", "install-hook", "--stage"]).is_err());
+    }
+
+    #[test]
+    fn add_requires_file() {
+        assert!(Args::try_parse_from(["docstring-rs", "add", "-d", "src"]).is_err());
     }
 }
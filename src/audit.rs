@@ -0,0 +1,301 @@
+/*
+* MIT License
+*
+* Copyright (c) 2023 Wilhelm Ågren
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*
+* File created: 2026-10-18
+* Last updated: 2026-10-18
+*/
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::header::is_leading;
+use crate::license::spdx_tag;
+use crate::{find_header, is_generated, uncomment, FileType, License};
+
+/// How many lines at the top of a file are searched for an `SPDX-License-Identifier` tag.
+static SPDX_LINES: usize = 10;
+
+/// The kind of leading header that a file has.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum HeaderKind {
+    /// A docstring generated by docstring-rs.
+    Docstring,
+    /// An `SPDX-License-Identifier` tag.
+    Spdx,
+    /// A comment with the text of a built-in license.
+    Known,
+    /// A comment which declares no license that could be recognised.
+    Unknown,
+    /// No leading comment at all.
+    Missing,
+    /// A binary file, which cannot have a header.
+    Binary,
+}
+
+impl fmt::Display for HeaderKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeaderKind::Docstring => write!(f, "docstring-rs header"),
+            HeaderKind::Spdx => write!(f, "SPDX tag"),
+            HeaderKind::Known => write!(f, "license text"),
+            HeaderKind::Unknown => write!(f, "unknown header"),
+            HeaderKind::Missing => write!(f, "no header"),
+            HeaderKind::Binary => write!(f, "binary file"),
+        }
+    }
+}
+
+/// The license that a file declares in its leading header.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Declaration {
+    /// The kind of header the license was declared in.
+    pub kind: HeaderKind,
+    /// The SPDX id of the declared license, if it could be recognised.
+    pub license: Option<String>,
+    /// Whether or not the declared license is the project license.
+    pub matches_project: bool,
+}
+
+impl Declaration {
+    /// The declaration of a binary file, which declares nothing and is never
+    /// counted as disagreeing.
+    pub fn binary() -> Self {
+        Declaration {
+            kind: HeaderKind::Binary,
+            license: None,
+            matches_project: false,
+        }
+    }
+
+    /// A name for the declared license to use in summaries.
+    pub fn license_name(&self) -> String {
+        match (&self.license, self.kind) {
+            (Some(id), _) => id.clone(),
+            (None, HeaderKind::Missing) => "none".to_owned(),
+            (None, HeaderKind::Binary) => "binary".to_owned(),
+            (None, _) if self.matches_project => "project LICENSE".to_owned(),
+            (None, _) => "unknown".to_owned(),
+        }
+    }
+
+    /// Whether or not the file declares a license other than the project license.
+    /// Files without a header, or with a header that declares no license, such as
+    /// a comment describing the file, do not.
+    pub fn disagrees(&self) -> bool {
+        !matches!(
+            self.kind,
+            HeaderKind::Unknown | HeaderKind::Missing | HeaderKind::Binary
+        ) && !self.matches_project
+    }
+}
+
+/// Classify the leading header of `contents` and the license it declares, comparing
+/// it against the `project` license.
+pub fn classify_str(contents: &str, file_type: FileType, project: &License) -> Declaration {
+    let top: String = contents
+        .lines()
+        .take(SPDX_LINES)
+        .collect::<Vec<&str>>()
+        .join("\n");
+    if let Some(tag) = spdx_tag(&top) {
+        let matches_project = project
+            .spdx()
+            .is_some_and(|id| id.eq_ignore_ascii_case(&tag));
+        return Declaration {
+            kind: HeaderKind::Spdx,
+            license: Some(tag),
+            matches_project,
+        };
+    }
+
+    let leading = file_type
+        .header_offsets(contents)
+        .into_iter()
+        .find_map(|from| {
+            let rest = &contents[from..];
+            file_type.comment_styles().into_iter().find_map(|cs| {
+                find_header(rest, &cs)
+                    .filter(|span| is_leading(rest, 0, span.start))
+                    .map(|span| (&rest[span], cs))
+            })
+        });
+    let (header, cs) = match leading {
+        Some(found) => found,
+        None => {
+            return Declaration {
+                kind: HeaderKind::Missing,
+                license: None,
+                matches_project: false,
+            }
+        }
+    };
+
+    let matches_project = project.matches(header, &cs);
+    let text = uncomment(header, &cs);
+    let license = License::from_text(&text).spdx().map(|id| id.to_owned());
+    let kind = match (is_generated(header), &license) {
+        (true, _) => HeaderKind::Docstring,
+        (false, Some(_)) => HeaderKind::Known,
+        (false, None) if matches_project => HeaderKind::Known,
+        (false, None) => HeaderKind::Unknown,
+    };
+
+    Declaration {
+        kind,
+        license,
+        matches_project,
+    }
+}
+
+/// The declared licenses of a set of files, summarised per license and directory.
+#[derive(Debug, Default)]
+pub struct Audit {
+    files: Vec<(PathBuf, Declaration)>,
+}
+
+impl Audit {
+    /// Record the declaration of the file at `path`.
+    pub fn add(&mut self, path: &Path, declaration: Declaration) {
+        self.files.push((path.to_path_buf(), declaration));
+    }
+
+    /// All recorded files and their declarations.
+    pub fn files(&self) -> &[(PathBuf, Declaration)] {
+        &self.files
+    }
+
+    /// The number of files declaring each license.
+    pub fn by_license(&self) -> BTreeMap<String, usize> {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for (_, d) in &self.files {
+            *counts.entry(d.license_name()).or_default() += 1;
+        }
+        counts
+    }
+
+    /// The number of files declaring each license, per directory. Files at the top
+    /// are counted under `.`.
+    pub fn by_directory(&self) -> BTreeMap<PathBuf, BTreeMap<String, usize>> {
+        let mut counts: BTreeMap<PathBuf, BTreeMap<String, usize>> = BTreeMap::new();
+        for (path, d) in &self.files {
+            let dir = match path.parent() {
+                Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+                _ => PathBuf::from("."),
+            };
+            *counts
+                .entry(dir)
+                .or_default()
+                .entry(d.license_name())
+                .or_default() += 1;
+        }
+        counts
+    }
+
+    /// All files declaring a license other than the project license.
+    pub fn disagreeing(&self) -> Vec<&(PathBuf, Declaration)> {
+        self.files.iter().filter(|(_, d)| d.disagrees()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests_audit {
+    use super::*;
+    use crate::apply_header_str;
+
+    fn mit() -> License {
+        License::resolve(Path::new("LICENSE")).unwrap()
+    }
+
+    #[test]
+    fn classify_docstring() {
+        let project = mit();
+        let (contents, _) =
+            apply_header_str("fn main() {}\n", project.text(), FileType::Rust).unwrap();
+        let d = classify_str(&contents, FileType::Rust, &project);
+        assert_eq!(d.kind, HeaderKind::Docstring);
+        assert_eq!(d.license.as_deref(), Some("MIT"));
+        assert!(!d.disagrees());
+    }
+
+    #[test]
+    fn classify_spdx_tag() {
+        let contents = "#!/usr/bin/env python3\n# SPDX-License-Identifier: Apache-2.0\nimport os\n";
+        let d = classify_str(contents, FileType::Python, &mit());
+        assert_eq!(d.kind, HeaderKind::Spdx);
+        assert_eq!(d.license_name(), "Apache-2.0");
+        assert!(d.disagrees());
+    }
+
+    #[test]
+    fn classify_known_unknown_missing() {
+        let gpl = License::from_spdx("GPL-3.0-or-later").unwrap();
+        let contents = "/*\n* ".to_owned() + &gpl.text().replace('\n', "\n* ") + "\n*/\nint x;\n";
+        let d = classify_str(&contents, FileType::C, &mit());
+        assert_eq!(d.kind, HeaderKind::Known);
+        assert_eq!(d.license_name(), "GPL-3.0-or-later");
+
+        let d = classify_str("/*\n* Some helpers.\n*/\nint x;\n", FileType::C, &mit());
+        assert_eq!(d.kind, HeaderKind::Unknown);
+        assert_eq!(d.license_name(), "unknown");
+        assert!(!d.disagrees());
+
+        let d = classify_str("int x;\n/*\n* Not leading.\n*/\n", FileType::C, &mit());
+        assert_eq!(d.kind, HeaderKind::Missing);
+        assert_eq!(d.license_name(), "none");
+        assert!(!d.disagrees());
+    }
+
+    #[test]
+    fn audit_summaries() {
+        let mut audit = Audit::default();
+        let declaration = |license: Option<&str>, matches_project: bool| Declaration {
+            kind: HeaderKind::Spdx,
+            license: license.map(|l| l.to_owned()),
+            matches_project,
+        };
+        audit.add(Path::new("src/a.rs"), declaration(Some("MIT"), true));
+        audit.add(Path::new("src/b.rs"), declaration(Some("MIT"), true));
+        audit.add(
+            Path::new("vendor/c.c"),
+            declaration(Some("BSD-3-Clause"), false),
+        );
+        audit.add(Path::new("build.rs"), declaration(Some("MIT"), true));
+
+        let by_license = audit.by_license();
+        assert_eq!(by_license.get("MIT"), Some(&3));
+        assert_eq!(by_license.get("BSD-3-Clause"), Some(&1));
+
+        let by_directory = audit.by_directory();
+        assert_eq!(by_directory[Path::new("src")].get("MIT"), Some(&2));
+        assert_eq!(
+            by_directory[Path::new("vendor")].get("BSD-3-Clause"),
+            Some(&1)
+        );
+        assert_eq!(by_directory[Path::new(".")].get("MIT"), Some(&1));
+
+        let disagreeing = audit.disagreeing();
+        assert_eq!(disagreeing.len(), 1);
+        assert_eq!(disagreeing[0].0, PathBuf::from("vendor/c.c"));
+    }
+}
@@ -0,0 +1,560 @@
+/*
+* MIT License
+*
+* Copyright (c) 2023 Wilhelm Ågren
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*
+* File created: 2026-10-18
+* Last updated: 2026-10-18
+*/
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use glob::{MatchOptions, Pattern};
+
+use crate::git::{current_author, line_authors, original_author};
+use crate::{Error, Result};
+
+/// The fields that docstrings can name their authors in, in the order they are
+/// written.
+pub const FIELDS: [&str; 2] = ["Author", "Maintainers"];
+
+/// Where GitHub and GitLab look for a `CODEOWNERS` file, in order.
+pub const CODEOWNERS_FILES: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// Where the authors named in docstrings are taken from.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum AuthorSource {
+    /// The git history of every file: the author of the commit that added it, and
+    /// the authors of most of its lines.
+    #[default]
+    Git,
+    /// The owners of every file in the `CODEOWNERS` file, in the order listed.
+    Codeowners,
+}
+
+impl FromStr for AuthorSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "git" => Ok(AuthorSource::Git),
+            "codeowners" => Ok(AuthorSource::Codeowners),
+            _ => Err(format!(
+                "unknown author source `{s}`, expected `git` or `codeowners`"
+            )),
+        }
+    }
+}
+
+/// A person or team named in a docstring, e.g. `Jane Doe <jane@example.com>` or
+/// the `@org/team` of a `CODEOWNERS` file.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Identity {
+    pub name: String,
+    pub email: Option<String>,
+}
+
+impl Identity {
+    /// Parse `Name <email>`, a bare email address or a name such as `@user`.
+    pub fn parse(s: &str) -> Identity {
+        let s = s.trim();
+        if let (Some(open), Some(close)) = (s.find('<'), s.rfind('>')) {
+            if open < close {
+                return Identity {
+                    name: s[..open].trim().to_owned(),
+                    email: Some(s[open + 1..close].trim().to_owned()),
+                };
+            }
+        }
+        match s.contains('@') && !s.starts_with('@') {
+            true => Identity {
+                name: String::new(),
+                email: Some(s.to_owned()),
+            },
+            false => Identity {
+                name: s.to_owned(),
+                email: None,
+            },
+        }
+    }
+
+    /// The identity as written in a docstring, with its email address spelled out
+    /// as `jane at example dot com` if `obfuscate` is set, to keep it from being
+    /// harvested.
+    pub fn render(&self, obfuscate: bool) -> String {
+        let email = match (&self.email, obfuscate) {
+            (Some(e), true) => Some(obfuscate_email(e)),
+            (e, _) => e.clone(),
+        };
+        match (self.name.is_empty(), email) {
+            (_, None) => self.name.clone(),
+            (true, Some(e)) => e,
+            (false, Some(e)) => format!("{} <{}>", self.name, e),
+        }
+    }
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(false))
+    }
+}
+
+/// Spell out `email` as `user at example dot com`.
+pub fn obfuscate_email(email: &str) -> String {
+    match email.rsplit_once('@') {
+        Some((user, domain)) => format!("{} at {}", user, domain.replace('.', " dot ")),
+        None => email.to_owned(),
+    }
+}
+
+/// A `.mailmap` file, which maps the names and email addresses that people have
+/// committed under to the ones they want to be known by.
+#[derive(Debug, Clone, Default)]
+pub struct Mailmap {
+    entries: Vec<MailmapEntry>,
+}
+
+/// A line of a `.mailmap` file, replacing the name and or email address of commits
+/// made with `commit_email`, and `commit_name` if given.
+#[derive(Debug, Clone)]
+struct MailmapEntry {
+    name: Option<String>,
+    email: Option<String>,
+    commit_name: Option<String>,
+    commit_email: String,
+}
+
+impl Mailmap {
+    /// Parse the contents of a `.mailmap` file. Lines that are not understood are
+    /// skipped, as git does.
+    pub fn parse(s: &str) -> Mailmap {
+        let entries = s
+            .lines()
+            .filter_map(|line| {
+                let line = line.split('#').next().unwrap_or_default();
+                let mut ids = Vec::new();
+                let mut rest = line;
+                while let (Some(open), Some(close)) = (rest.find('<'), rest.find('>')) {
+                    if close < open {
+                        break;
+                    }
+                    let name = rest[..open].trim();
+                    let name = (!name.is_empty()).then(|| name.to_owned());
+                    ids.push((name, rest[open + 1..close].trim().to_owned()));
+                    rest = &rest[close + 1..];
+                }
+                match ids.as_slice() {
+                    [(name, email)] => Some(MailmapEntry {
+                        name: name.clone(),
+                        email: None,
+                        commit_name: None,
+                        commit_email: email.clone(),
+                    }),
+                    [(name, email), (commit_name, commit_email)] => Some(MailmapEntry {
+                        name: name.clone(),
+                        email: Some(email.clone()),
+                        commit_name: commit_name.clone(),
+                        commit_email: commit_email.clone(),
+                    }),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        Mailmap { entries }
+    }
+
+    /// Read the `.mailmap` file at `path`.
+    pub fn load(path: &Path) -> Result<Mailmap> {
+        fs::read_to_string(path)
+            .map(|s| Mailmap::parse(&s))
+            .map_err(|e| Error::Read {
+                path: path.to_path_buf(),
+                source: e,
+            })
+    }
+
+    /// The identity that `identity` is known by. Entries naming the commit name as
+    /// well as the email address take precedence.
+    pub fn resolve(&self, identity: &Identity) -> Identity {
+        let email = match &identity.email {
+            Some(e) => e,
+            None => return identity.clone(),
+        };
+        let matching = |e: &&MailmapEntry| e.commit_email.eq_ignore_ascii_case(email);
+        let entry = self
+            .entries
+            .iter()
+            .filter(matching)
+            .find(|e| e.commit_name.as_deref() == Some(identity.name.as_str()))
+            .or_else(|| {
+                self.entries
+                    .iter()
+                    .filter(matching)
+                    .find(|e| e.commit_name.is_none())
+            });
+        match entry {
+            Some(e) => Identity {
+                name: e.name.clone().unwrap_or_else(|| identity.name.clone()),
+                email: e.email.clone().or_else(|| identity.email.clone()),
+            },
+            None => identity.clone(),
+        }
+    }
+}
+
+/// A `CODEOWNERS` file, which assigns owners to the files matching its patterns.
+#[derive(Debug, Clone, Default)]
+pub struct Codeowners {
+    rules: Vec<OwnerRule>,
+}
+
+/// A line of a `CODEOWNERS` file. Patterns containing a slash other than a trailing
+/// one are anchored to the top of the repository, others match at any depth.
+#[derive(Debug, Clone)]
+struct OwnerRule {
+    pattern: Pattern,
+    anchored: bool,
+    owners: Vec<String>,
+}
+
+impl Codeowners {
+    /// Parse the contents of a `CODEOWNERS` file.
+    pub fn parse(s: &str) -> Result<Codeowners> {
+        let mut rules = Vec::new();
+        for line in s.lines() {
+            let mut words = line.split_whitespace();
+            let pattern = match words.next() {
+                Some(p) if !p.starts_with('#') => p,
+                _ => continue,
+            };
+            let owners = words
+                .take_while(|w| !w.starts_with('#'))
+                .map(str::to_owned)
+                .collect();
+
+            let anchored = pattern.trim_end_matches('/').contains('/');
+            let mut glob = pattern.trim_start_matches('/').to_owned();
+            if glob.ends_with('/') {
+                glob.push_str("**");
+            }
+            let pattern = Pattern::new(&glob).map_err(|e| Error::Glob {
+                pattern: pattern.to_owned(),
+                reason: e.to_string(),
+            })?;
+            rules.push(OwnerRule {
+                pattern,
+                anchored,
+                owners,
+            });
+        }
+
+        Ok(Codeowners { rules })
+    }
+
+    /// Read the `CODEOWNERS` file at `path`.
+    pub fn load(path: &Path) -> Result<Codeowners> {
+        let contents = fs::read_to_string(path).map_err(|e| Error::Read {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        Codeowners::parse(&contents).map_err(|e| e.with_path(path))
+    }
+
+    /// Read the first `CODEOWNERS` file found in `dir`, the top of a repository.
+    pub fn find(dir: &Path) -> Result<Option<Codeowners>> {
+        CODEOWNERS_FILES
+            .iter()
+            .map(|f| dir.join(f))
+            .find(|p| p.is_file())
+            .map(|p| Codeowners::load(&p))
+            .transpose()
+    }
+
+    /// The owners of the file at `path`, relative to the top of the repository,
+    /// as given by the last rule matching it or any of its directories.
+    pub fn owners(&self, path: &Path) -> &[String] {
+        let path: PathBuf = path
+            .components()
+            .filter(|c| !matches!(c, Component::CurDir))
+            .collect();
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        let matches = |rule: &OwnerRule, p: &Path| {
+            let components: Vec<_> = p.components().collect();
+            let tails = match rule.anchored {
+                true => 0..1,
+                false => 0..components.len(),
+            };
+            tails.into_iter().any(|i| {
+                let tail: PathBuf = components[i..].iter().collect();
+                rule.pattern.matches_path_with(&tail, options)
+            })
+        };
+
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                path.ancestors()
+                    .filter(|a| !a.as_os_str().is_empty())
+                    .any(|a| matches(rule, a))
+            })
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// Settings for naming the authors of every file in its docstring, on an
+/// `Author:` line and a `Maintainers:` line.
+#[derive(Debug, Clone)]
+pub struct Authors {
+    /// Where the authors are taken from.
+    pub source: AuthorSource,
+    /// Write an `Author:` line with the original author of the file.
+    pub author: bool,
+    /// Write a `Maintainers:` line with up to this many people, those who wrote
+    /// most of the lines of the file, or the first owners in `CODEOWNERS`. Left out
+    /// if zero.
+    pub maintainers: usize,
+    /// Spell out email addresses as `jane at example dot com`.
+    pub obfuscate: bool,
+    /// Maps the identities found to the ones people want to be known by.
+    pub mailmap: Mailmap,
+    /// The owners of the files, if the authors are taken from `CODEOWNERS`.
+    pub codeowners: Codeowners,
+    /// The canonical directory that the paths in git and `CODEOWNERS` are relative
+    /// to: the top of the git repository, or the directory of the config file for
+    /// `CODEOWNERS` outside of one. No fields are written without it.
+    pub root: Option<PathBuf>,
+}
+
+impl Default for Authors {
+    fn default() -> Self {
+        Self {
+            source: AuthorSource::Git,
+            author: true,
+            maintainers: 0,
+            obfuscate: false,
+            mailmap: Mailmap::default(),
+            codeowners: Codeowners::default(),
+            root: None,
+        }
+    }
+}
+
+impl Authors {
+    /// The `Author:` and `Maintainers:` fields of the docstring of the file at
+    /// `path`, as pairs of name and value. The lines in `header`, the existing
+    /// docstring, are not counted when looking for the authors of most lines.
+    /// Files that were never committed are authored by the current git user.
+    /// Files outside of `root` get no fields.
+    pub fn fields(&self, path: &Path, header: Range<usize>) -> Result<Vec<(String, String)>> {
+        let (root, path) = match self
+            .root
+            .as_deref()
+            .and_then(|r| Some((r, relative_to(r, path)?)))
+        {
+            Some(found) => found,
+            None => return Ok(Vec::new()),
+        };
+        let (author, maintainers) = match self.source {
+            AuthorSource::Git => self.git_authors(root, &path, header)?,
+            AuthorSource::Codeowners => {
+                let owners: Vec<Identity> = self
+                    .codeowners
+                    .owners(&path)
+                    .iter()
+                    .map(|o| self.mailmap.resolve(&Identity::parse(o)))
+                    .collect();
+                (owners.first().cloned(), owners)
+            }
+        };
+
+        let mut fields = Vec::new();
+        if let (true, Some(author)) = (self.author, author) {
+            fields.push((FIELDS[0].to_owned(), author.render(self.obfuscate)));
+        }
+        if self.maintainers > 0 && !maintainers.is_empty() {
+            let names: Vec<String> = maintainers
+                .iter()
+                .take(self.maintainers)
+                .map(|m| m.render(self.obfuscate))
+                .collect();
+            fields.push((FIELDS[1].to_owned(), names.join(", ")));
+        }
+
+        Ok(fields)
+    }
+
+    /// The original author of `path`, relative to the top `dir` of its repository,
+    /// and everyone who wrote lines of it outside of `header`, those with the most
+    /// lines first.
+    fn git_authors(
+        &self,
+        dir: &Path,
+        path: &Path,
+        header: Range<usize>,
+    ) -> Result<(Option<Identity>, Vec<Identity>)> {
+        let author = match self.author {
+            true => original_author(dir, path)?.or_else(|| current_author(dir)),
+            false => None,
+        };
+        let author = author.map(|a| self.mailmap.resolve(&Identity::parse(&a)));
+        if self.maintainers == 0 {
+            return Ok((author, Vec::new()));
+        }
+
+        let mut lines: HashMap<Identity, usize> = HashMap::new();
+        for (i, line) in line_authors(dir, path)?.into_iter().enumerate() {
+            if let (false, Some(a)) = (header.contains(&i), line) {
+                *lines
+                    .entry(self.mailmap.resolve(&Identity::parse(&a)))
+                    .or_default() += 1;
+            }
+        }
+        let mut maintainers: Vec<(Identity, usize)> = lines.into_iter().collect();
+        maintainers.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.name.cmp(&b.name)));
+
+        Ok((author, maintainers.into_iter().map(|(m, _)| m).collect()))
+    }
+}
+
+/// `path` relative to the canonical directory `root`, or `None` if it is outside
+/// of it. Only the directory of `path` is resolved, as the file may not exist yet.
+fn relative_to(root: &Path, path: &Path) -> Option<PathBuf> {
+    let dir = match path.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d,
+        _ => Path::new("."),
+    };
+    let path = dir.canonicalize().ok()?.join(path.file_name()?);
+    path.strip_prefix(root).ok().map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests_authors {
+    use super::*;
+
+    #[test]
+    fn identity_render() {
+        let jane = Identity::parse("Jane Doe <jane.doe@mail.example.com>");
+        assert_eq!(jane.name, "Jane Doe");
+        assert_eq!(jane.to_string(), "Jane Doe <jane.doe@mail.example.com>");
+        assert_eq!(
+            jane.render(true),
+            "Jane Doe <jane.doe at mail dot example dot com>"
+        );
+        assert_eq!(Identity::parse("@org/team").render(true), "@org/team");
+        assert_eq!(
+            Identity::parse("ops@example.com").render(true),
+            "ops at example dot com"
+        );
+    }
+
+    #[test]
+    fn mailmap_resolve() {
+        let mailmap = Mailmap::parse(
+            "# people
+Jane Doe <jane@example.com>
+<jane@example.com> <jd@old.example.com>
+Joe Bloggs <joe@example.com> <joe@laptop>
+Joe B <joe@example.com> joe <JOE@work>
+",
+        );
+        let resolve = |s: &str| mailmap.resolve(&Identity::parse(s)).to_string();
+        assert_eq!(
+            resolve("jane <jane@example.com>"),
+            "Jane Doe <jane@example.com>"
+        );
+        assert_eq!(resolve("jd <jd@old.example.com>"), "jd <jane@example.com>");
+        assert_eq!(resolve("root <joe@laptop>"), "Joe Bloggs <joe@example.com>");
+        assert_eq!(resolve("joe <joe@work>"), "Joe B <joe@example.com>");
+        assert_eq!(resolve("other <joe@work>"), "other <joe@work>");
+        assert_eq!(resolve("@team"), "@team");
+    }
+
+    #[test]
+    fn codeowners_last_match_wins() {
+        let codeowners = Codeowners::parse(
+            "# owners
+* @everyone
+*.rs @rustaceans # inline comment
+/src/vendor/ @vendors ops@example.com
+docs @writers
+",
+        )
+        .unwrap();
+        let owners = |p: &str| codeowners.owners(Path::new(p)).join(" ");
+        assert_eq!(owners("README.md"), "@everyone");
+        assert_eq!(owners("./src/main.rs"), "@rustaceans");
+        assert_eq!(owners("src/vendor/lib.rs"), "@vendors ops@example.com");
+        assert_eq!(owners("lib/src/vendor/lib.rs"), "@rustaceans");
+        assert_eq!(owners("guide/docs/intro.md"), "@writers");
+        assert!(Codeowners::parse("[ @nobody\n").is_err());
+    }
+
+    #[test]
+    fn fields_from_codeowners() {
+        let authors = Authors {
+            source: AuthorSource::Codeowners,
+            maintainers: 2,
+            obfuscate: true,
+            mailmap: Mailmap::parse("Ops Team <ops@example.com>\n"),
+            codeowners: Codeowners::parse("src/ ops@example.com @a @b\n").unwrap(),
+            root: Some(std::env::current_dir().unwrap().canonicalize().unwrap()),
+            ..Default::default()
+        };
+        let fields = authors.fields(Path::new("src/lib.rs"), 0..0).unwrap();
+        assert_eq!(
+            fields,
+            vec![
+                (
+                    "Author".to_owned(),
+                    "Ops Team <ops at example dot com>".to_owned()
+                ),
+                (
+                    "Maintainers".to_owned(),
+                    "Ops Team <ops at example dot com>, @a".to_owned()
+                ),
+            ]
+        );
+        assert!(authors
+            .fields(Path::new("README.md"), 0..0)
+            .unwrap()
+            .is_empty());
+        let outside = Authors {
+            root: None,
+            ..authors
+        };
+        assert!(outside
+            .fields(Path::new("src/lib.rs"), 0..0)
+            .unwrap()
+            .is_empty());
+    }
+}
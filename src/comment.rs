@@ -22,27 +22,86 @@
 * SOFTWARE.
 *
 * File created: 2023-10-01
-* Last updated: 2023-10-04
+* Last updated: 2026-10-18
 */
 
-///
+use std::fmt;
+use std::str::FromStr;
+
+/// Whether docstrings are written as one multiline comment, or as a line comment
+/// on every line.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Style {
+    Block,
+    Line,
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Style::Block => write!(f, "block"),
+            Style::Line => write!(f, "line"),
+        }
+    }
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "block" => Ok(Style::Block),
+            "line" => Ok(Style::Line),
+            _ => Err(format!(
+                "unknown comment style `{s}`, expected `block` or `line`"
+            )),
+        }
+    }
+}
+
+/// The comment markers used to open, continue and close a multiline comment. A
+/// line comment style has no markers to open and close it, every line is prefixed
+/// with the normal comment marker instead.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct CommentStyle<'a> {
     multi_line_start: &'a str,
     normal_comment: &'a str,
     multi_line_end: &'a str,
+    padded: bool,
 }
 
-///
 impl<'a> CommentStyle<'a> {
     pub fn new(start: &'a str, normal: &'a str, end: &'a str) -> Self {
         CommentStyle {
             multi_line_start: start,
             normal_comment: normal,
             multi_line_end: end,
+            padded: false,
         }
     }
 
+    /// A line comment style prefixing every line with `prefix`, e.g. `// `.
+    pub fn line(prefix: &'a str) -> Self {
+        Self::new("", prefix, "")
+    }
+
+    /// Separate the text of the comment from its markers with blank lines, which
+    /// Perl POD needs to recognise the markers as commands.
+    pub fn padded(mut self) -> Self {
+        self.padded = true;
+        self
+    }
+
+    /// Whether or not the text is separated from the markers with blank lines.
+    pub fn is_padded(&self) -> bool {
+        self.padded
+    }
+
+    /// Whether or not this is a line comment style.
+    pub fn is_line(&self) -> bool {
+        self.multi_line_start.is_empty() && self.multi_line_end.is_empty()
+    }
+
     pub fn start(&self) -> &'a str {
         self.multi_line_start
     }
@@ -69,5 +128,17 @@ mod tests_comment {
         assert_eq!(cs.start(), start);
         assert_eq!(cs.normal(), normal);
         assert_eq!(cs.end(), end);
+        assert!(!cs.is_line());
+        assert!(!cs.is_padded());
+        assert!(cs.padded().is_padded());
+    }
+
+    #[test]
+    fn line_style() {
+        let cs = CommentStyle::line("// ");
+        assert!(cs.is_line());
+        assert_eq!(cs.normal(), "// ");
+        assert_eq!("Line".parse::<Style>(), Ok(Style::Line));
+        assert!("lines".parse::<Style>().is_err());
     }
 }
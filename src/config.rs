@@ -0,0 +1,807 @@
+/*
+* MIT License
+*
+* Copyright (c) 2023 Wilhelm Ågren
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*
+* File created: 2026-10-18
+* Last updated: 2026-10-18
+*/
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::git;
+use crate::{
+    AuthorSource, Authors, Codeowners, CommentStyle, Error, FileType, Language, LanguageMap,
+    Mailmap, Options, Result, Style,
+};
+
+/// The name of the config file that is used if none is given explicitly.
+pub const CONFIG_FILE: &str = ".docstring.toml";
+
+/// The name of the mailmap file that is used if none is given explicitly.
+const MAILMAP_FILE: &str = ".mailmap";
+
+/// Settings read from a config file, e.g.
+///
+/// ```toml
+/// license = "Apache-2.0"
+/// style = "line"
+///
+/// [map]
+/// ".pl" = "Prolog"
+/// "include/*.h" = "C++"
+///
+/// [[language]]
+/// name = "Jsonnet"
+/// extensions = ["jsonnet", "libsonnet"]
+/// block = ["/*", "* ", "*/"]
+/// line = "// "
+///
+/// [authors]
+/// source = "git"
+/// maintainers = 3
+/// obfuscate = true
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// Globs, or file endings such as `.pl`, pinning the files they match to a
+    /// language.
+    pub languages: LanguageMap,
+    /// The languages declared in the config file, which are registered as file
+    /// types when it is parsed.
+    pub custom: Vec<FileType>,
+    /// How to name the authors of every file in its docstring, if at all.
+    pub authors: Option<Authors>,
+    /// The LICENSE file to use as docstring, or the SPDX id of a built-in license,
+    /// if none is given on the command line.
+    pub license: Option<PathBuf>,
+    /// The comment style to write docstrings in instead of the default style of
+    /// their language, if none is given on the command line.
+    pub style: Option<Style>,
+    /// Whether or not to leave the files alone, e.g. in a directory of vendored
+    /// code.
+    pub skip: Option<bool>,
+    /// The config files in the subdirectories of the project.
+    pub overrides: Option<Overrides>,
+}
+
+/// The config file as written by the user, before it is validated.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawConfig {
+    license: Option<String>,
+    style: Option<String>,
+    skip: Option<bool>,
+    map: toml::Table,
+    language: Vec<RawLanguage>,
+    authors: Option<RawAuthors>,
+}
+
+/// The `[authors]` table of the config file.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawAuthors {
+    source: String,
+    author: bool,
+    maintainers: usize,
+    obfuscate: bool,
+    mailmap: Option<String>,
+    codeowners: Option<String>,
+}
+
+impl Default for RawAuthors {
+    fn default() -> Self {
+        Self {
+            source: "git".to_owned(),
+            author: true,
+            maintainers: 0,
+            obfuscate: false,
+            mailmap: None,
+            codeowners: None,
+        }
+    }
+}
+
+impl RawAuthors {
+    /// Validate the settings, reading the mailmap and `CODEOWNERS` files they use
+    /// relative to `dir`, the directory of the config file. The `.mailmap` file at
+    /// the top of the git repository is used if there is one, and `CODEOWNERS` is
+    /// looked for there where GitHub looks for it. Authors are only taken from git
+    /// inside of a repository.
+    fn build(self, dir: &Path) -> Result<Authors> {
+        let invalid = |reason: String| Error::Config {
+            path: PathBuf::new(),
+            reason,
+        };
+
+        let source: AuthorSource = self.source.parse().map_err(invalid)?;
+        let repository = git::toplevel(dir).ok().and_then(|t| t.canonicalize().ok());
+        let root = match (source, repository) {
+            (_, Some(top)) => Some(top),
+            (AuthorSource::Codeowners, None) => dir.canonicalize().ok(),
+            (AuthorSource::Git, None) => None,
+        };
+        let top = root.as_deref().unwrap_or(dir);
+
+        let mailmap = match self.mailmap {
+            Some(p) => Mailmap::load(&dir.join(p))?,
+            None if top.join(MAILMAP_FILE).is_file() => Mailmap::load(&top.join(MAILMAP_FILE))?,
+            None => Mailmap::default(),
+        };
+        let codeowners = match (source, self.codeowners) {
+            (_, Some(p)) => Codeowners::load(&dir.join(p))?,
+            (AuthorSource::Codeowners, None) => Codeowners::find(top)?
+                .ok_or_else(|| invalid("no CODEOWNERS file was found".to_owned()))?,
+            (AuthorSource::Git, None) => Codeowners::default(),
+        };
+
+        Ok(Authors {
+            source,
+            author: self.author,
+            maintainers: self.maintainers,
+            obfuscate: self.obfuscate,
+            mailmap,
+            codeowners,
+            root,
+        })
+    }
+}
+
+/// A language declared with `[[language]]` in the config file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawLanguage {
+    name: String,
+    extensions: Vec<String>,
+    filenames: Vec<String>,
+    interpreters: Vec<String>,
+    block: Option<[String; 3]>,
+    line: Option<String>,
+    style: Option<String>,
+    preamble: Vec<String>,
+}
+
+impl RawLanguage {
+    /// Validate the declared language and add it to the registry of file types.
+    fn register(self) -> Result<FileType> {
+        let invalid = |reason: String| Error::Config {
+            path: PathBuf::new(),
+            reason,
+        };
+
+        let name = leak(self.name);
+        let endings: Vec<&str> = self
+            .extensions
+            .into_iter()
+            .map(|e| leak(e.trim_start_matches('.').to_owned()))
+            .collect();
+        let names: Vec<&str> = self.filenames.into_iter().map(leak).collect();
+        let interpreters: Vec<&str> = self.interpreters.into_iter().map(leak).collect();
+        let line = self.line.map(leak);
+        let block = match (self.block, line) {
+            (Some([start, normal, end]), _) => {
+                CommentStyle::new(leak(start), leak(normal), leak(end))
+            }
+            (None, Some(prefix)) => CommentStyle::line(prefix),
+            (None, None) => {
+                return Err(invalid(format!(
+                    "language `{name}` has neither a block nor a line comment"
+                )))
+            }
+        };
+        let mut language = Language::new(name, block)
+            .with_endings(&endings)
+            .with_file_names(&names)
+            .with_interpreters(&interpreters);
+        if let Some(prefix) = line {
+            language = language.with_line_comment(prefix);
+        }
+        if let Some(style) = self.style {
+            let style: Style = style
+                .parse()
+                .map_err(|e| invalid(format!("language `{name}`: {e}")))?;
+            language = language.with_style(style);
+        }
+        for pattern in self.preamble {
+            let regex = Regex::new(&format!("^(?:{pattern})")).map_err(|e| {
+                invalid(format!(
+                    "language `{name}`: invalid preamble `{pattern}`: {e}"
+                ))
+            })?;
+            language = language.with_preamble(regex);
+        }
+
+        language.register()
+    }
+}
+
+/// Keep `s` alive for the rest of the program, as registered languages are.
+fn leak(s: String) -> &'static str {
+    Box::leak(s.into_boxed_str())
+}
+
+impl Config {
+    /// Read the config file at `path`. A `license` naming a file next to the config
+    /// file is read from there.
+    pub fn load(path: &Path) -> Result<Config> {
+        let contents = fs::read_to_string(path).map_err(|e| Error::Read {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        let dir = match path.parent() {
+            Some(d) if !d.as_os_str().is_empty() => d,
+            _ => Path::new("."),
+        };
+        let mut config = Config::parse_in(&contents, dir).map_err(|e| e.with_path(path))?;
+        if let Some(license) = &config.license {
+            if dir.join(license).is_file() {
+                config.license = Some(dir.join(license));
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Read the config file at `path` if given, otherwise the `.docstring.toml` at
+    /// the top of the git repository containing the current directory, or in the
+    /// current directory outside of a repository. The config files in the
+    /// subdirectories below it override it for the files below them.
+    pub fn resolve(path: Option<&Path>) -> Result<Config> {
+        Config::resolve_from(Path::new("."), path)
+    }
+
+    /// Resolve the config file as `resolve` does when run from `dir`.
+    pub fn resolve_from(dir: &Path, path: Option<&Path>) -> Result<Config> {
+        let (root, config) = match path {
+            Some(p) => {
+                let root = match p.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                    _ => PathBuf::from("."),
+                };
+                (root, Config::load(p)?)
+            }
+            None => {
+                let root = git::toplevel(dir).unwrap_or_else(|_| dir.to_path_buf());
+                let top = root.join(CONFIG_FILE);
+                match top.is_file() {
+                    true => (root, Config::load(&top)?),
+                    false => (root, Config::default()),
+                }
+            }
+        };
+
+        Ok(Config {
+            overrides: Some(Overrides::new(&root)?),
+            ..config
+        })
+    }
+
+    /// Read the config file at `path` in a subdirectory, which may not declare
+    /// languages or pin files to them, as those apply to the whole project.
+    fn load_override(path: &Path) -> Result<Config> {
+        let config = Config::load(path)?;
+        if !config.languages.is_empty() || !config.custom.is_empty() {
+            return Err(Error::Config {
+                path: path.to_path_buf(),
+                reason: format!(
+                    "`[map]` and `[[language]]` can only be set in the top `{CONFIG_FILE}`"
+                ),
+            });
+        }
+
+        Ok(config)
+    }
+
+    /// Apply the settings of the config file to `options`, keeping the options
+    /// that it does not set and those that were given explicitly.
+    pub fn apply(&self, mut options: Options) -> Options {
+        if let (Some(license), false) = (&self.license, options.explicit.license) {
+            options.license = license.clone();
+        }
+        if let (Some(style), false) = (self.style, options.explicit.style) {
+            options.style = Some(style);
+        }
+        if let Some(skip) = self.skip {
+            options.skip = skip;
+        }
+        if !self.languages.is_empty() {
+            options.languages = self.languages.clone();
+        }
+        if self.authors.is_some() {
+            options.authors = self.authors.clone();
+        }
+        if self.overrides.is_some() {
+            options.overrides = self.overrides.clone();
+        }
+        options
+    }
+}
+
+impl FromStr for Config {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Config::parse_in(s, Path::new("."))
+    }
+}
+
+impl Config {
+    /// Parse the contents `s` of a config file in `dir`, which the files it names
+    /// are relative to.
+    fn parse_in(s: &str, dir: &Path) -> Result<Config> {
+        let invalid = |reason: String| Error::Config {
+            path: PathBuf::new(),
+            reason,
+        };
+
+        let raw: RawConfig = toml::from_str(s).map_err(|e| invalid(e.message().to_owned()))?;
+        let custom = raw
+            .language
+            .into_iter()
+            .map(RawLanguage::register)
+            .collect::<Result<Vec<FileType>>>()?;
+
+        let mut languages = LanguageMap::default();
+        for (pattern, language) in raw.map {
+            let name = language
+                .as_str()
+                .ok_or_else(|| invalid(format!("the language of `{pattern}` is not a string")))?;
+            let file_type = FileType::try_from_name(name)
+                .map_err(|_| invalid(format!("unknown language `{name}` for `{pattern}`")))?;
+            languages = languages
+                .with_pin(&pattern, file_type)
+                .map_err(|e| invalid(e.to_string()))?;
+        }
+
+        let authors = raw.authors.map(|a| a.build(dir)).transpose()?;
+        let style = raw
+            .style
+            .map(|s| s.parse::<Style>())
+            .transpose()
+            .map_err(invalid)?;
+
+        Ok(Config {
+            languages,
+            custom,
+            authors,
+            license: raw.license.map(PathBuf::from),
+            style,
+            skip: raw.skip,
+            overrides: None,
+        })
+    }
+}
+
+/// The config files in the subdirectories of a project, each overriding the
+/// settings of the directories above it for the files below it, so that the
+/// nearest config file wins. They are read as they are first needed.
+#[derive(Debug, Clone)]
+pub struct Overrides {
+    root: PathBuf,
+    found: Arc<Mutex<HashMap<PathBuf, Option<Arc<Config>>>>>,
+}
+
+impl Overrides {
+    /// Look for config files in the subdirectories of `root`, whose own config
+    /// file is not one of them.
+    pub fn new(root: &Path) -> Result<Overrides> {
+        let root = root.canonicalize().map_err(|e| Error::Read {
+            path: root.to_path_buf(),
+            source: e,
+        })?;
+        Ok(Overrides {
+            root,
+            found: Arc::default(),
+        })
+    }
+
+    /// The config files of the directories containing `path` below the root,
+    /// nearest first. A file that does not exist yet is looked up from its nearest
+    /// existing directory.
+    pub fn configs(&self, path: &Path) -> Result<Vec<Arc<Config>>> {
+        let dir = match path
+            .ancestors()
+            .skip(1)
+            .map(|a| match a.as_os_str().is_empty() {
+                true => Path::new("."),
+                false => a,
+            })
+            .find_map(|a| a.canonicalize().ok())
+        {
+            Some(d) => d,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut configs = Vec::new();
+        let mut found = self.found.lock().expect("config cache was poisoned");
+        for dir in dir.ancestors().take_while(|d| *d != self.root) {
+            if !dir.starts_with(&self.root) {
+                return Ok(Vec::new());
+            }
+            let config = match found.get(dir) {
+                Some(c) => c.clone(),
+                None => {
+                    let file = dir.join(CONFIG_FILE);
+                    let config = match file.is_file() {
+                        true => Some(Arc::new(Config::load_override(&file)?)),
+                        false => None,
+                    };
+                    found.insert(dir.to_path_buf(), config.clone());
+                    config
+                }
+            };
+            configs.extend(config);
+        }
+
+        Ok(configs)
+    }
+
+    /// Whether or not the nearest config file setting `skip` for `path` leaves it
+    /// alone.
+    pub fn skips(&self, path: &Path) -> Result<bool> {
+        Ok(self
+            .configs(path)?
+            .iter()
+            .find_map(|c| c.skip)
+            .unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+mod tests_config {
+    use super::*;
+
+    #[test]
+    fn parse_map() {
+        let config: Config = "[map]\n\".pl\" = \"Prolog\"\n\"include/*.h\" = \"c++\"\n"
+            .parse()
+            .unwrap();
+        let languages = &config.languages;
+        assert_eq!(
+            languages.pinned(Path::new("bin/run.pl")),
+            Some(FileType::Prolog)
+        );
+        assert_eq!(
+            languages.pinned(Path::new("include/a.h")),
+            Some(FileType::CPP)
+        );
+        assert!("".parse::<Config>().unwrap().languages.is_empty());
+    }
+
+    #[test]
+    fn parse_language() {
+        let config: Config = r##"
+            [map]
+            "templates/*.tpl" = "Mustache"
+
+            [[language]]
+            name = "Mustache"
+            extensions = [".mustache"]
+            block = ["{{!", "", "}}"]
+
+            [[language]]
+            name = "Janet"
+            extensions = ["janet"]
+            interpreters = ["janet"]
+            line = "# "
+            style = "line"
+            preamble = ['\(use ']
+        "##
+        .parse()
+        .unwrap();
+
+        let [mustache, janet] = config.custom[..] else {
+            panic!("expected two languages, got {:?}", config.custom);
+        };
+        assert_eq!(
+            config.languages.pinned(Path::new("templates/a.tpl")),
+            Some(mustache)
+        );
+        assert_eq!(FileType::try_from_filename("a.mustache").unwrap(), mustache);
+        assert_eq!(mustache.get_comment_style().start(), "{{!");
+        assert_eq!(janet.get_comment_style(), CommentStyle::line("# "));
+        assert_eq!(
+            janet.preamble_len(
+                "#!/usr/bin/env janet
+(use sh)
+(print 1)
+"
+            ),
+            30
+        );
+    }
+
+    #[test]
+    fn parse_language_errors() {
+        let builtin = "[[language]]\nname = \"Rust\"\nline = \"// \"\n";
+        let e = builtin.parse::<Config>().unwrap_err();
+        assert!(e.to_string().contains("built in as `Rust`"));
+        let mute = "[[language]]\nname = \"Mute\"\n"
+            .parse::<Config>()
+            .unwrap_err();
+        assert!(mute
+            .to_string()
+            .contains("neither a block nor a line comment"));
+        assert!(
+            "[[language]]\nname = \"Odd\"\nline = \"# \"\nstyle = \"wavy\"\n"
+                .parse::<Config>()
+                .is_err()
+        );
+        assert!(
+            "[[language]]\nname = \"Odd\"\nline = \"# \"\npreamble = [\"(\"]\n"
+                .parse::<Config>()
+                .is_err()
+        );
+        assert!("[[language]]\nname = \"Odd\"\ncomment = \"# \"\n"
+            .parse::<Config>()
+            .is_err());
+    }
+
+    #[test]
+    fn parse_authors() {
+        assert!("".parse::<Config>().unwrap().authors.is_none());
+        let config: Config = "[authors]\nmaintainers = 3\nobfuscate = true\n"
+            .parse()
+            .unwrap();
+        let authors = config.authors.unwrap();
+        assert_eq!(authors.source, AuthorSource::Git);
+        assert!(authors.author && authors.obfuscate);
+        assert_eq!(authors.maintainers, 3);
+
+        let source = "[authors]\nsource = \"blame\"\n"
+            .parse::<Config>()
+            .unwrap_err();
+        assert!(source.to_string().contains("unknown author source `blame`"));
+        let missing = "[authors]\nsource = \"codeowners\"\n".parse::<Config>();
+        assert!(missing.unwrap_err().to_string().contains("no CODEOWNERS"));
+        let mailmap = "[authors]\nmailmap = \"missing.mailmap\"\n".parse::<Config>();
+        assert!(matches!(mailmap, Err(Error::Read { .. })));
+        assert!("[authors]\nemails = false\n".parse::<Config>().is_err());
+    }
+
+    #[test]
+    fn authors_relative_to_repository() {
+        let root = std::env::temp_dir().join(crate::tmp::random_file_name());
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("CODEOWNERS"), "src/ @core\n").unwrap();
+        let config = root.join(CONFIG_FILE);
+        fs::write(&config, "[authors]\nsource = \"codeowners\"\n").unwrap();
+        let outside = Config::load(&config).unwrap().authors.unwrap();
+        let author = vec![("Author".to_owned(), "@core".to_owned())];
+        assert_eq!(
+            outside.fields(&root.join("src/a.rs"), 0..0).unwrap(),
+            author
+        );
+
+        git::init(&root).unwrap();
+        fs::write(root.join("src").join(CONFIG_FILE), "style = \"line\"\n").unwrap();
+        let authors = Config::resolve_from(&root.join("src"), None)
+            .unwrap()
+            .authors
+            .unwrap();
+        assert_eq!(
+            authors.fields(&root.join("src/a.rs"), 0..0).unwrap(),
+            author
+        );
+        assert!(authors
+            .fields(Path::new("src/lib.rs"), 0..0)
+            .unwrap()
+            .is_empty());
+
+        fs::remove_dir_all(root.join(".git")).unwrap();
+        fs::write(&config, "[authors]\n").unwrap();
+        let git = Config::load(&config).unwrap().authors.unwrap();
+        assert!(git.fields(&root.join("src/a.rs"), 0..0).unwrap().is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn parse_overrides() {
+        let config: Config = "license = \"Apache-2.0\"\nstyle = \"line\"\nskip = false\n"
+            .parse()
+            .unwrap();
+        assert_eq!(config.license, Some(PathBuf::from("Apache-2.0")));
+        let options = config.apply(Options::new("LICENSE").with_force(true));
+        assert_eq!(options.license, PathBuf::from("Apache-2.0"));
+        assert_eq!(options.style, Some(Style::Line));
+        assert!(options.force && !options.skip);
+        assert!("style = \"wavy\"\n".parse::<Config>().is_err());
+    }
+
+    #[test]
+    fn overrides_nearest_wins() {
+        let root = std::env::temp_dir().join(crate::tmp::random_file_name());
+        fs::create_dir_all(root.join("libs/core/src")).unwrap();
+        fs::create_dir_all(root.join("vendor")).unwrap();
+        fs::write(root.join(CONFIG_FILE), "skip = true\n").unwrap();
+        fs::write(
+            root.join("libs").join(CONFIG_FILE),
+            "license = \"MIT\"\nskip = true\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("libs/core").join(CONFIG_FILE),
+            "license = \"LICENSE\"\nskip = false\n",
+        )
+        .unwrap();
+        fs::write(root.join("libs/core/LICENSE"), "Core License\n").unwrap();
+        fs::write(
+            root.join("vendor").join(CONFIG_FILE),
+            "[map]\n\".x\" = \"C\"\n",
+        )
+        .unwrap();
+
+        let overrides = Overrides::new(&root).unwrap();
+        let configs = overrides
+            .configs(&root.join("libs/core/src/new/a.rs"))
+            .unwrap();
+        assert_eq!(configs.len(), 2);
+        assert!(!overrides.skips(&root.join("libs/core/src/a.rs")).unwrap());
+        assert!(overrides.skips(&root.join("libs/b.rs")).unwrap());
+        assert!(!overrides.skips(&root.join("main.rs")).unwrap());
+        assert!(overrides
+            .configs(Path::new("/etc/hosts"))
+            .unwrap()
+            .is_empty());
+        assert!(overrides.configs(&root.join("vendor/a.c")).is_err());
+
+        let options = Options::new("LICENSE").with_overrides(Some(overrides));
+        let libs = options.for_path(&root.join("libs/b.rs")).unwrap();
+        assert_eq!(libs.license, PathBuf::from("MIT"));
+        let core = options.for_path(&root.join("libs/core/src/a.rs")).unwrap();
+        assert_eq!(core.license, root.join("libs/core/LICENSE"));
+        assert!(!core.skip);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn options_layers() {
+        let root = std::env::temp_dir().join(crate::tmp::random_file_name());
+        fs::create_dir_all(root.join("apps")).unwrap();
+        fs::write(
+            root.join(CONFIG_FILE),
+            "license = \"Apache-2.0\"\nstyle = \"block\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("apps").join(CONFIG_FILE),
+            "license = \"MIT\"\nstyle = \"line\"\n",
+        )
+        .unwrap();
+        let config = Config::load(&root.join(CONFIG_FILE)).unwrap();
+        let overrides = Some(Overrides::new(&root).unwrap());
+
+        // built-in default
+        let options = Options::default();
+        assert_eq!(options.license, PathBuf::from("LICENSE"));
+        assert_eq!(options.style, None);
+
+        // root config
+        let options = config
+            .apply(Options::default())
+            .with_overrides(overrides.clone());
+        let top = options.for_path(&root.join("a.rs")).unwrap();
+        assert_eq!(top.license, PathBuf::from("Apache-2.0"));
+        assert_eq!(top.style, Some(Style::Block));
+
+        // subdirectory config
+        let apps = options.for_path(&root.join("apps/a.rs")).unwrap();
+        assert_eq!(apps.license, PathBuf::from("MIT"));
+        assert_eq!(apps.style, Some(Style::Line));
+
+        // explicit options, e.g. `--license` and `--style`
+        let options = config
+            .apply(
+                Options::default()
+                    .with_license("COPYING")
+                    .with_style(Style::Block),
+            )
+            .with_overrides(overrides);
+        assert_eq!(options.license, PathBuf::from("COPYING"));
+        let apps = options.for_path(&root.join("apps/a.rs")).unwrap();
+        assert_eq!(apps.license, PathBuf::from("COPYING"));
+        assert_eq!(apps.style, Some(Style::Block));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn parse_errors() {
+        let unknown = "[map]\n\".x\" = \"Kebab\"\n".parse::<Config>().unwrap_err();
+        assert!(unknown.to_string().contains("unknown language `Kebab`"));
+        assert!("[map]\n\".x\" = 1\n".parse::<Config>().is_err());
+        assert!("[colours]\n".parse::<Config>().is_err());
+        assert!("[map".parse::<Config>().is_err());
+    }
+
+    #[test]
+    fn resolve_from_subdirectory() {
+        let root = std::env::temp_dir().join(crate::tmp::random_file_name());
+        let sub = root.join("third_party/sub");
+        fs::create_dir_all(&sub).unwrap();
+        git::init(&root).unwrap();
+        fs::write(root.join(CONFIG_FILE), "style = \"line\"\n").unwrap();
+        fs::write(root.join("third_party").join(CONFIG_FILE), "skip = true\n").unwrap();
+
+        let config = Config::resolve_from(&sub, None).unwrap();
+        assert_eq!(config.style, Some(Style::Line));
+        let overrides = config.overrides.unwrap();
+        assert!(overrides.skips(&sub.join("vendored.c")).unwrap());
+        assert!(!overrides.skips(&root.join("main.c")).unwrap());
+
+        let explicit = root.join("third_party").join(CONFIG_FILE);
+        let config = Config::resolve_from(&sub, Some(&explicit)).unwrap();
+        assert!(!config
+            .overrides
+            .unwrap()
+            .skips(&sub.join("vendored.c"))
+            .unwrap());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn readme_monorepo_example() {
+        let readme = include_str!("../README.md");
+        let start = readme.find("# libs/.docstring.toml").unwrap();
+        let example = &readme[start..start + readme[start..].find("```").unwrap()];
+
+        let root = std::env::temp_dir().join(crate::tmp::random_file_name());
+        let mut file = None;
+        for line in example.lines() {
+            match line.strip_prefix("# ") {
+                Some(name) if name.ends_with(CONFIG_FILE) => {
+                    let path = root.join(name);
+                    fs::create_dir_all(path.parent().unwrap()).unwrap();
+                    fs::write(path.with_file_name("LICENSE"), "Copyright (c) 2024 ACME\n").unwrap();
+                    file = Some(path);
+                }
+                _ => {
+                    let path = file.as_ref().unwrap();
+                    let contents = fs::read_to_string(path).unwrap_or_default();
+                    fs::write(path, contents + line + "\n").unwrap();
+                }
+            }
+        }
+
+        let options = Options::new("MIT").with_overrides(Some(Overrides::new(&root).unwrap()));
+        for dir in ["libs", "apps"] {
+            let options = options.for_path(&root.join(dir).join("a.c")).unwrap();
+            assert_eq!(options.license, root.join(dir).join("LICENSE"));
+            assert_eq!(options.read_license().unwrap().placeholder(), None);
+        }
+        let options = options.for_path(&root.join("third_party/a.c")).unwrap();
+        assert!(options.skip);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn load_missing() {
+        let e = Config::load(Path::new("missing.toml")).unwrap_err();
+        assert_eq!(e.path(), Some(Path::new("missing.toml")));
+        assert!(Config::resolve(None).is_ok());
+    }
+}
@@ -0,0 +1,505 @@
+/*
+* MIT License
+*
+* Copyright (c) 2023 Wilhelm Ågren
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*
+* File created: 2026-10-18
+* Last updated: 2026-10-18
+*/
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use glob::Pattern;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::walk::matches_tail;
+use crate::{Error, FileType, Result};
+
+/// File endings used by more than one language, whose `FileType` is decided from
+/// the contents of the file, with the supported languages using them and the names
+/// of the unsupported ones.
+const AMBIGUOUS: &[(&str, &[FileType], &[&str])] = &[
+    ("pl", &[FileType::Perl, FileType::Prolog], &[]),
+    ("t", &[FileType::Perl], &[]),
+    ("l", &[FileType::Lisp], &["Lex", "Flex"]),
+    ("r", &[FileType::R], &["REBOL"]),
+    ("fs", &[FileType::FSharp], &["GLSL"]),
+];
+
+/// How many bytes at the start of a file are read to decide its language.
+const SNIFF_LEN: u64 = 8 * 1024;
+
+/// How many bytes at the end of a longer file are also read, for its modeline.
+const TAIL_LEN: u64 = 1024;
+
+/// How many lines at the start and at the end of a file may hold a modeline, as
+/// with the default `modelines` setting of Vim.
+const MODELINES: usize = 5;
+
+/// Magic numbers at the start of binary files that may have a source file ending:
+/// archives, compressed data, serialized R data and native code.
+const MAGIC: &[&[u8]] = &[
+    b"PK\x03\x04",
+    b"\x1f\x8b",
+    b"\xfd7zXZ\x00",
+    b"RDX2\n",
+    b"RDX3\n",
+    b"\x7fELF",
+    b"\xca\xfe\xba\xbe",
+    b"\xcf\xfa\xed\xfe",
+];
+
+/// A Vim modeline setting the file type, e.g. `# vim: set ft=perl:`.
+static VIM_MODELINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:^|\s)(?:vim?|ex):.*\b(?:ft|filetype|syntax)=([\w+#-]+)")
+        .expect("could not compile regex")
+});
+
+/// An Emacs modeline setting the major mode, e.g. `-*- mode: prolog -*-` or
+/// `-*- perl -*-`.
+static EMACS_MODELINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"-\*-\s*(?:.*\bmode:\s*([\w+#-]+)|([\w+#-]+))\s*(?:;.*)?-\*-")
+        .expect("could not compile regex")
+});
+
+/// Perl code, e.g. `use strict;`, `my $x` or `sub main {`.
+static PERL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^\s*(use\s+(strict|warnings|v?5)|package\s+[\w:]+;|sub\s+\w+\s*\{|my\s+[$@%]|=head1|=pod)|\$_\b|Test::More")
+        .expect("could not compile regex")
+});
+
+/// Prolog code, e.g. `:- module(foo, []).` or `parent(X, Y) :- father(X, Y).`
+static PROLOG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^\s*:-|^[a-z]\w*(\(.*\))?\s*:-").expect("could not compile regex")
+});
+
+/// Lex or Flex code, which separates its sections with `%%` lines.
+static LEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^(%%|%\{|%option\b)").expect("could not compile regex"));
+
+/// A REBOL script, which starts with a `REBOL [...]` header.
+static REBOL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?im)^\s*REBOL\s*\[").expect("could not compile regex"));
+
+/// A GLSL shader, e.g. `#version 330 core` or `gl_FragColor`.
+static GLSL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^\s*(#version\s+\d+|precision\s+(lowp|mediump|highp)\b|(uniform|varying|attribute)\s+\w+\s+\w+)|\bgl_(FragColor|FragCoord|Position)\b")
+        .expect("could not compile regex")
+});
+
+/// Whether or not `contents`, the first bytes of a file, belong to a binary file:
+/// they start with a known magic number or contain a NUL byte.
+pub fn is_binary(contents: &[u8]) -> bool {
+    let head = &contents[..contents.len().min(SNIFF_LEN as usize)];
+    MAGIC.iter().any(|m| head.starts_with(m)) || head.contains(&0)
+}
+
+/// Whether or not the file ending of `fname` is used by more than one language.
+pub fn is_ambiguous(fname: &str) -> bool {
+    Path::new(fname)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| AMBIGUOUS.iter().any(|(ending, ..)| *ending == e))
+}
+
+/// The `FileType` of a script run by the interpreter of its shebang line, e.g.
+/// `#!/usr/bin/env perl`, as declared by the languages in the registry.
+pub fn from_shebang(contents: &str) -> Option<FileType> {
+    let line = contents.strip_prefix("#!")?.lines().next()?;
+    let mut words = line.split_whitespace();
+    let mut interpreter = words.next()?.rsplit('/').next()?;
+    if interpreter == "env" {
+        interpreter = words.find(|w| !w.starts_with('-'))?;
+    }
+
+    let name = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    FileType::from_interpreter(name)
+}
+
+/// The language name set by a Vim or Emacs modeline in `contents`, e.g. `perl`.
+/// Like the editors themselves, only looks for a Vim modeline in the first and
+/// last few lines, and for an Emacs one in the first line, or in the second one
+/// after a shebang.
+pub fn from_modeline(contents: &str) -> Option<String> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let tail = lines.len().saturating_sub(MODELINES).max(MODELINES);
+    let edges = lines.iter().take(MODELINES).chain(lines.iter().skip(tail));
+    if let Some(c) = edges.filter_map(|l| VIM_MODELINE.captures(l)).next() {
+        return Some(c[1].to_owned());
+    }
+
+    let first = match lines.first()?.starts_with("#!") {
+        true => lines.get(1)?,
+        false => lines[0],
+    };
+    let c = EMACS_MODELINE.captures(first)?;
+    c.get(1).or(c.get(2)).map(|m| m.as_str().to_owned())
+}
+
+/// The `FileType` of a script from its `contents`, using the interpreter of its
+/// shebang or otherwise its Vim or Emacs modeline.
+pub fn from_contents(contents: &str) -> Option<FileType> {
+    from_shebang(contents).or_else(|| {
+        let name = from_modeline(contents)?;
+        FileType::try_from_name(&name).ok()
+    })
+}
+
+/// Decide the `FileType` of a file with the ambiguous file `ending` from its
+/// `contents`, using its shebang, modeline and finally keywords of the languages.
+/// A shebang or modeline only counts if it names a language using `ending`.
+/// Returns `Error::UnsupportedLanguage` if the file is written in a language that
+/// is recognised but not supported, such as Lex for `.l` or GLSL for `.fs`.
+pub fn disambiguate(ending: &str, contents: &str) -> Result<FileType> {
+    let (supported, unsupported) = match AMBIGUOUS.iter().find(|(e, ..)| *e == ending) {
+        Some((_, supported, unsupported)) => (*supported, *unsupported),
+        None => {
+            return FileType::try_from_filename(&format!("file.{ending}")).map_err(|_| {
+                Error::UnsupportedFileType {
+                    path: PathBuf::new(),
+                }
+            })
+        }
+    };
+    let unsupported_language = |language: &str| Error::UnsupportedLanguage {
+        path: PathBuf::new(),
+        language: language.to_owned(),
+    };
+
+    if let Some(ft) = from_shebang(contents).filter(|ft| supported.contains(ft)) {
+        return Ok(ft);
+    }
+    if let Some(name) = from_modeline(contents) {
+        if let Some(ft) = FileType::try_from_name(&name)
+            .ok()
+            .filter(|ft| supported.contains(ft))
+        {
+            return Ok(ft);
+        }
+        if let Some(language) = unsupported.iter().find(|l| l.eq_ignore_ascii_case(&name)) {
+            return Err(unsupported_language(language));
+        }
+    }
+
+    use FileType::*;
+    match ending {
+        "pl" if PROLOG.is_match(contents) && !PERL.is_match(contents) => Ok(Prolog),
+        "l" if LEX.is_match(contents) => Err(unsupported_language("Lex")),
+        "r" if REBOL.is_match(contents) => Err(unsupported_language("REBOL")),
+        "fs" if GLSL.is_match(contents) => Err(unsupported_language("GLSL")),
+        _ => Ok(supported[0]),
+    }
+}
+
+/// Find the `FileType` of the file at `path` from its name, looking at its contents
+/// if its file ending is ambiguous and not claimed by a language registered at
+/// runtime, or if it has no file ending at all, e.g. `bin/deploy`. Files that
+/// cannot be read, e.g. because they do not exist yet, are only judged by their
+/// name.
+pub fn detect(path: &Path) -> Result<FileType> {
+    let fname = path.to_string_lossy();
+    let file_type = match FileType::try_from_filename(&fname) {
+        Ok(ft) => ft,
+        Err(e) if path.extension().is_none() => {
+            return sniff(path)
+                .and_then(|contents| from_contents(&contents))
+                .ok_or(e);
+        }
+        Err(e) => return Err(e),
+    };
+    if file_type.is_custom() || !is_ambiguous(&fname) {
+        return Ok(file_type);
+    }
+
+    let contents = match sniff(path) {
+        Some(c) => c,
+        None => return Ok(file_type),
+    };
+    let ending = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    disambiguate(ending, &contents).map_err(|e| e.with_path(path))
+}
+
+/// The first bytes of the file at `path`, followed by its last bytes if it is any
+/// longer, if it can be read and is not binary.
+fn sniff(path: &Path) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    let mut contents = Vec::new();
+    file.by_ref()
+        .take(SNIFF_LEN)
+        .read_to_end(&mut contents)
+        .ok()?;
+    if is_binary(&contents) {
+        return None;
+    }
+
+    let len = file.metadata().ok()?.len();
+    if len > SNIFF_LEN {
+        let mut tail = Vec::new();
+        file.seek(SeekFrom::Start(len.saturating_sub(TAIL_LEN).max(SNIFF_LEN)))
+            .and_then(|_| file.take(TAIL_LEN).read_to_end(&mut tail))
+            .ok()?;
+        contents.push(b'\n');
+        contents.extend(tail);
+    }
+    Some(String::from_utf8_lossy(&contents).into_owned())
+}
+
+/// Globs pinning the files they match to a `FileType`, which take precedence over
+/// the file name and contents of the files.
+#[derive(Debug, Clone, Default)]
+pub struct LanguageMap {
+    pins: Vec<(Pattern, FileType)>,
+}
+
+impl LanguageMap {
+    /// Pin files matching `pattern` to `file_type`. A pattern starting with a dot,
+    /// e.g. `.pl`, pins all files with that file ending. Earlier pins win.
+    pub fn with_pin(mut self, pattern: &str, file_type: FileType) -> Result<Self> {
+        let glob = match pattern.starts_with('.') && !pattern.contains('/') {
+            true => format!("*{pattern}"),
+            false => pattern.to_owned(),
+        };
+        match Pattern::new(&glob) {
+            Ok(p) => self.pins.push((p, file_type)),
+            Err(e) => {
+                return Err(Error::Glob {
+                    pattern: pattern.to_owned(),
+                    reason: e.to_string(),
+                })
+            }
+        };
+        Ok(self)
+    }
+
+    /// Whether or not no files are pinned.
+    pub fn is_empty(&self) -> bool {
+        self.pins.is_empty()
+    }
+
+    /// The `FileType` that `path` is pinned to, if any.
+    pub fn pinned(&self, path: &Path) -> Option<FileType> {
+        self.pins
+            .iter()
+            .find(|(p, _)| matches_tail(p, path))
+            .map(|(_, ft)| *ft)
+    }
+
+    /// The `FileType` of the file at `path`, pinned or otherwise detected.
+    pub fn resolve(&self, path: &Path) -> Result<FileType> {
+        match self.pinned(path) {
+            Some(ft) => Ok(ft),
+            None => detect(path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_detect {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn is_binary_ok() {
+        assert!(is_binary(b"\x55\x0d\x0d\x0a\x00\x00\x00\x00"));
+        assert!(is_binary(b"PK\x03\x04rest of the zip"));
+        assert!(is_binary(b"RDX3\nX\n"));
+        assert!(!is_binary(b"fn main() {}\n"));
+        assert!(!is_binary("# Ågren\n".as_bytes()));
+        assert!(!is_binary(b""));
+    }
+
+    #[test]
+    fn from_shebang_ok() {
+        assert_eq!(from_shebang("#!/usr/bin/perl -w\n"), Some(FileType::Perl));
+        assert_eq!(
+            from_shebang("#!/usr/bin/env -S swipl -q\n"),
+            Some(FileType::Prolog)
+        );
+        assert_eq!(from_shebang("#!/usr/bin/perl5.36\n"), Some(FileType::Perl));
+        assert_eq!(from_shebang("#!/usr/bin/python3\n"), Some(FileType::Python));
+        assert_eq!(from_shebang("#!/bin/bash -e\n"), Some(FileType::Shell));
+        assert_eq!(
+            from_shebang("#!/usr/bin/env node\n"),
+            Some(FileType::JavaScript)
+        );
+        assert_eq!(
+            from_shebang("#!/usr/bin/env ruby3.2\n"),
+            Some(FileType::Ruby)
+        );
+        assert_eq!(from_shebang("#!/bin/frobnicate\n"), None);
+        assert_eq!(from_shebang("print 1;\n"), None);
+    }
+
+    #[test]
+    fn from_modeline_ok() {
+        assert_eq!(
+            from_modeline("% vim: set ft=prolog:\n").as_deref(),
+            Some("prolog")
+        );
+        assert_eq!(
+            from_modeline("; -*- mode: lisp; coding: utf-8 -*-\n").as_deref(),
+            Some("lisp")
+        );
+        assert_eq!(from_modeline("# -*- perl -*-\n").as_deref(), Some("perl"));
+        assert_eq!(from_modeline("no modeline here\n"), None);
+    }
+
+    #[test]
+    fn from_contents_ok() {
+        assert_eq!(
+            from_contents("#!/usr/bin/env perl\n# vim: ft=python\n"),
+            Some(FileType::Perl)
+        );
+        assert_eq!(
+            from_contents("# -*- mode: shell-script -*-\necho hi\n"),
+            Some(FileType::Shell)
+        );
+        assert_eq!(
+            from_contents("# vim: set ft=make:\nall:\n"),
+            Some(FileType::Makefile)
+        );
+        assert_eq!(from_contents("MIT License\n"), None);
+    }
+
+    #[test]
+    fn disambiguate_ok() {
+        use FileType::*;
+        let ok = |ending, contents| disambiguate(ending, contents).unwrap();
+        let unsupported = |ending, contents| match disambiguate(ending, contents) {
+            Err(Error::UnsupportedLanguage { language, .. }) => language,
+            r => panic!("expected an unsupported language, got {:?}", r),
+        };
+        assert_eq!(ok("pl", "use strict;\nmy $x = 1;\n"), Perl);
+        assert_eq!(
+            ok("pl", ":- module(family, []).\nparent(X) :- father(X).\n"),
+            Prolog
+        );
+        assert_eq!(ok("pl", "% vim: ft=prolog\nfoo.\n"), Prolog);
+        assert_eq!(ok("pl", "print 'hi';\n"), Perl);
+        assert_eq!(ok("t", "use Test::More;\n"), Perl);
+        assert_eq!(unsupported("l", "%{\n#include <stdio.h>\n%}\n%%\n"), "Lex");
+        assert_eq!(unsupported("l", "/* vim: set ft=lex: */\n"), "Lex");
+        assert_eq!(ok("l", "(defun f (x) x)\n"), Lisp);
+        assert_eq!(unsupported("r", "REBOL [Title: \"Hi\"]\n"), "REBOL");
+        assert_eq!(ok("r", "x <- c(1, 2)\n"), R);
+        assert_eq!(
+            unsupported("fs", "#version 330 core\nvoid main() {}\n"),
+            "GLSL"
+        );
+        assert_eq!(ok("fs", "let x = 1\n"), FSharp);
+        assert!(matches!(
+            disambiguate("kebab", ""),
+            Err(Error::UnsupportedFileType { .. })
+        ));
+    }
+
+    #[test]
+    fn disambiguate_ignores_other_languages() {
+        use FileType::*;
+        let stray = |ending, contents| disambiguate(ending, contents).unwrap();
+        assert_eq!(stray("l", "#!/usr/bin/env python\n(defun f (x) x)\n"), Lisp);
+        assert_eq!(stray("r", "# vim: set ft=python:\nx <- 1\n"), R);
+        assert_eq!(stray("fs", "// vim: ft=frobnicate\nlet x = 1\n"), FSharp);
+        assert_eq!(stray("pl", "# -*- mode: ruby -*-\nmy $x;\n"), Perl);
+    }
+
+    #[test]
+    fn from_modeline_at_the_edges() {
+        let body = "x <- 1\n".repeat(20);
+        let middle = format!("{body}# vim: set ft=python:\n{body}");
+        let last = format!("{body}# vim: set ft=python:\n");
+        assert_eq!(from_modeline(&middle), None);
+        assert_eq!(from_modeline(&last).as_deref(), Some("python"));
+        assert_eq!(from_modeline("call svim:ft=x\n"), None);
+        assert_eq!(
+            from_modeline("#!/bin/sh\n# -*- mode: perl -*-\n").as_deref(),
+            Some("perl")
+        );
+        assert_eq!(from_modeline("\n\n# -*- mode: perl -*-\n"), None);
+    }
+
+    #[test]
+    fn detect_ok() {
+        assert_eq!(detect(Path::new("src/detect.rs")).unwrap(), FileType::Rust);
+        assert_eq!(detect(Path::new("missing/run.pl")).unwrap(), FileType::Perl);
+        assert!(detect(Path::new("LICENSE")).is_err());
+    }
+
+    #[test]
+    fn detect_extensionless() {
+        let dir = std::env::temp_dir().join(crate::tmp::random_file_name());
+        fs::create_dir_all(&dir).unwrap();
+        let deploy = dir.join("deploy");
+        let release = dir.join("release");
+        let notes = dir.join("NOTES");
+        fs::write(&deploy, "#!/usr/bin/env python3\nprint('hi')\n").unwrap();
+        fs::write(&release, "# vim: set ft=ruby:\nputs 1\n").unwrap();
+        fs::write(&notes, "Remember the milk.\n").unwrap();
+
+        assert_eq!(detect(&deploy).unwrap(), FileType::Python);
+        assert_eq!(detect(&release).unwrap(), FileType::Ruby);
+        assert!(detect(&notes).is_err());
+        assert!(detect(&dir.join("missing")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn detect_unsupported_language() {
+        let dir = std::env::temp_dir().join(crate::tmp::random_file_name());
+        fs::create_dir_all(&dir).unwrap();
+        let scanner = dir.join("scanner.l");
+        let long = dir.join("long.pl");
+        fs::write(&scanner, "%%\n[0-9]+ { return NUMBER; }\n%%\n").unwrap();
+        let body = "foo.\n".repeat(2000);
+        fs::write(&long, format!("{body}% vim: set ft=prolog:\n")).unwrap();
+
+        match detect(&scanner) {
+            Err(Error::UnsupportedLanguage { path, language }) => {
+                assert_eq!(path, scanner);
+                assert_eq!(language, "Lex");
+            }
+            r => panic!("expected an unsupported language, got {:?}", r),
+        }
+        assert_eq!(detect(&long).unwrap(), FileType::Prolog);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn language_map_pins() {
+        let map = LanguageMap::default()
+            .with_pin("shaders/*.fs", FileType::C)
+            .unwrap()
+            .with_pin(".pl", FileType::Prolog)
+            .unwrap();
+        assert_eq!(map.pinned(Path::new("src/shaders/a.fs")), Some(FileType::C));
+        assert_eq!(map.pinned(Path::new("bin/run.pl")), Some(FileType::Prolog));
+        assert_eq!(map.pinned(Path::new("src/main.fs")), None);
+        assert_eq!(
+            map.resolve(Path::new("src/main.rs")).unwrap(),
+            FileType::Rust
+        );
+        assert!(LanguageMap::default().with_pin("[", FileType::C).is_err());
+        assert!(LanguageMap::default().is_empty());
+    }
+}
@@ -22,10 +22,10 @@
 * SOFTWARE.
 *
 * File created: 2023-10-02
-* Last updated: 2023-10-04
+* Last updated: 2026-10-18
 */
 
-use crate::FileType;
+use crate::{CommentStyle, Error, FileType, Result};
 
 use log::info;
 
@@ -35,118 +35,180 @@ use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
-///
+/// A docstring header built from the contents of a LICENSE file.
 pub struct Docstring {
     target_path: PathBuf,
     license_path: PathBuf,
-    file_type: FileType,
+    comment_style: CommentStyle<'static>,
     contents: Option<String>,
     formatted_contents: Option<String>,
     file_created: Option<String>,
+    fields: Vec<(String, String)>,
 }
 
-///
 impl Docstring {
-    ///
+    /// Create a docstring for `target_path` which reads its license from `license_path`.
     pub fn new(target_path: PathBuf, license_path: PathBuf, file_type: FileType) -> Self {
         Self {
             target_path,
             license_path,
-            file_type,
+            comment_style: file_type.get_comment_style(),
             contents: None,
             formatted_contents: None,
             file_created: None,
+            fields: Vec::new(),
         }
     }
 
-    ///
-    fn try_get_contents(&self) -> Result<String, io::Error> {
+    /// Create a docstring from license text that is already in memory.
+    pub fn from_license(license: &str, file_type: FileType) -> Self {
+        Self {
+            target_path: PathBuf::new(),
+            license_path: PathBuf::new(),
+            comment_style: file_type.get_comment_style(),
+            contents: Some(license.to_owned()),
+            formatted_contents: None,
+            file_created: None,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Write the docstring in `comment_style` instead of the default style of its
+    /// file type.
+    pub fn with_comment_style(mut self, comment_style: CommentStyle<'static>) -> Self {
+        self.comment_style = comment_style;
+        self
+    }
+
+    /// Add a `name: value` line, e.g. `Author: Jane Doe`, after the license contents.
+    pub fn with_field(mut self, name: &str, value: &str) -> Self {
+        self.fields.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// The license contents, errors if the LICENSE file has not been read yet.
+    fn try_get_contents(&self) -> Result<String> {
         match &self.contents {
             Some(c) => Ok(c.clone()),
-            None => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "you have not read the contents of the LICENSE file yet",
-            )),
+            None => Err(Error::LicenseNotFound {
+                path: self.license_path.clone(),
+                source: io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "you have not read the contents of the LICENSE file yet",
+                ),
+            }),
         }
     }
 
-    ///
+    /// Whether or not the target file already exists.
     pub fn target_exists(&self) -> bool {
         Path::new(&self.target_path).exists()
     }
 
-    ///
-    pub fn try_find_created_date(&mut self) -> Result<(), io::Error> {
+    /// The created date found in an existing docstring, if any.
+    pub fn file_created(&self) -> Option<&str> {
+        self.file_created.as_deref()
+    }
+
+    /// Try and find the created date of an existing docstring in the target file.
+    pub fn try_find_created_date(&mut self) -> Result<()> {
         let fc = match fs::read_to_string(&self.target_path) {
             Ok(c) => c,
-            Err(e) => return Err(e),
+            Err(e) => {
+                return Err(Error::Read {
+                    path: self.target_path.clone(),
+                    source: e,
+                })
+            }
         };
+        match self.find_created_date(&fc) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::MalformedHeader {
+                path: self.target_path.clone(),
+                reason: "could not find a created date".to_owned(),
+            }),
+            Err(e) => Err(e.with_path(&self.target_path)),
+        }
+    }
 
+    /// Look for the created date of an existing docstring in `contents`, returns
+    /// whether or not a date was found.
+    pub fn find_created_date(&mut self, contents: &str) -> Result<bool> {
         let re = Regex::new(r"\d{4}\-(0?[1-9]|1[012])\-(0?[0-9]|[12][0-9]|3[01])*")
             .expect("could not compile regex");
-        for line in fc.split('\n') {
+        for line in contents.split('\n') {
             if line.contains("File created: ") {
                 let date: String = match re.find(line) {
                     Some(d) => d.as_str().to_owned(),
                     None => {
-                        return Err(io::Error::new(
-                            io::ErrorKind::NotFound,
-                            "could not parse date",
-                        ))
+                        return Err(Error::MalformedHeader {
+                            path: PathBuf::new(),
+                            reason: format!("could not parse date from `{}`", line.trim()),
+                        })
                     }
                 };
                 self.file_created = Some(date);
-                return Ok(());
+                return Ok(true);
             };
         }
 
-        Err(io::Error::new(
-            io::ErrorKind::NotFound,
-            "could not find a created date",
-        ))
-    }
-
-    ///
-    pub fn try_read_license(&mut self) -> Result<(), io::Error> {
-        match fs::read_to_string(&self.license_path) {
-            Ok(c) => {
-                info!(
-                    "Read contents of {} successfully",
-                    &self.license_path.display()
-                );
-                self.contents = Some(c);
-                Ok(())
+        Ok(false)
+    }
+
+    /// Read the contents of the LICENSE file.
+    pub fn try_read_license(&mut self) -> Result<()> {
+        let c = match fs::read_to_string(&self.license_path) {
+            Ok(c) => c,
+            Err(e) => {
+                return Err(Error::LicenseNotFound {
+                    path: self.license_path.clone(),
+                    source: e,
+                })
             }
-            Err(e) => Err(e),
-        }
+        };
+        info!(
+            "Read contents of {} successfully",
+            &self.license_path.display()
+        );
+        self.contents = Some(c);
+        Ok(())
     }
 
     pub fn get_formatted_contents(self) -> Option<String> {
         self.formatted_contents
     }
 
-    ///
-    pub fn format_contents(&mut self) -> Result<(), io::Error> {
-        let contents = match self.try_get_contents() {
-            Ok(c) => c,
-            Err(e) => return Err(e),
-        };
+    /// Format the license contents as a comment using the file type's `CommentStyle`.
+    pub fn format_contents(&mut self) -> Result<()> {
+        let contents = self.try_get_contents()?;
 
-        let style = self.file_type.get_comment_style();
+        let style = &self.comment_style;
         let start = style.start();
         let comment = style.normal();
         let end = style.end();
 
         let mut formatted = String::new();
 
-        // start the multiline comment
-        formatted.push_str(start);
-        formatted.push('\n');
+        // start the multiline comment, line comments have no start
+        if !style.is_line() {
+            formatted.push_str(start);
+            formatted.push('\n');
+        }
+        if style.is_padded() {
+            formatted.push('\n');
+        }
 
-        // add conents of the LICENSE file
+        // add conents of the LICENSE file, without trailing whitespace on empty lines
         for line in contents.split('\n') {
+            let commented = comment.to_owned() + line;
+            formatted.push_str(commented.trim_end());
+            formatted.push('\n');
+        }
+
+        // add fields such as the authors
+        for (name, value) in &self.fields {
             formatted.push_str(comment);
-            formatted.push_str(line);
+            formatted.push_str(&format!("{}: {}", name, value));
             formatted.push('\n');
         }
 
@@ -167,9 +229,14 @@ impl Docstring {
         formatted.push_str(local.as_str());
         formatted.push('\n');
 
-        // end the multiline comment
-        formatted.push_str(end);
-        formatted.push('\n');
+        // end the multiline comment, line comments have no end
+        if style.is_padded() {
+            formatted.push('\n');
+        }
+        if !style.is_line() {
+            formatted.push_str(end);
+            formatted.push('\n');
+        }
 
         self.formatted_contents = Some(formatted);
 
@@ -206,4 +273,38 @@ mod tests_docstring {
         ds.try_find_created_date().unwrap();
         ds.format_contents().unwrap();
     }
+
+    #[test]
+    fn format_contents_from_license() {
+        let mut ds = Docstring::from_license("MIT License", FileType::Rust);
+        ds.find_created_date("* File created: 2023-10-02\n")
+            .unwrap();
+        assert_eq!(ds.file_created(), Some("2023-10-02"));
+        ds.format_contents().unwrap();
+        let formatted = ds.get_formatted_contents().unwrap();
+        assert!(formatted.starts_with("/*\n* MIT License\n* File created: 2023-10-02\n"));
+        assert!(formatted.ends_with("*/\n"));
+    }
+
+    #[test]
+    fn format_contents_fields() {
+        let mut ds = Docstring::from_license("MIT License\n", FileType::Python)
+            .with_field("Author", "Jane Doe")
+            .with_field("Maintainers", "@a, @b");
+        ds.format_contents().unwrap();
+        let formatted = ds.get_formatted_contents().unwrap();
+        assert!(
+            formatted.contains("\n#\n# Author: Jane Doe\n# Maintainers: @a, @b\n# File created: ")
+        );
+    }
+
+    #[test]
+    fn format_contents_line_style() {
+        let mut ds = Docstring::from_license("MIT License\n", FileType::Rust)
+            .with_comment_style(CommentStyle::line("// "));
+        ds.format_contents().unwrap();
+        let formatted = ds.get_formatted_contents().unwrap();
+        assert!(formatted.starts_with("// MIT License\n//\n// File created: "));
+        assert!(formatted.lines().all(|l| l.starts_with("//")));
+    }
 }
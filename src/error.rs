@@ -0,0 +1,334 @@
+/*
+* MIT License
+*
+* Copyright (c) 2023 Wilhelm Ågren
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*
+* File created: 2026-10-18
+* Last updated: 2026-10-18
+*/
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Convenience alias used throughout the library.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors that can occur while creating, updating or checking docstrings. Every
+/// error carries the path it concerns, which is empty for in-memory contents.
+#[derive(Debug)]
+pub enum Error {
+    /// The LICENSE file could not be read.
+    LicenseNotFound { path: PathBuf, source: io::Error },
+    /// The file name did not map to any supported `FileType`.
+    UnsupportedFileType { path: PathBuf },
+    /// The file is written in a language that is recognised but not supported,
+    /// e.g. Lex for a `.l` file.
+    UnsupportedLanguage { path: PathBuf, language: String },
+    /// The file has a docstring which could not be understood.
+    MalformedHeader { path: PathBuf, reason: String },
+    /// The file contents were not valid UTF-8.
+    Decode { path: PathBuf },
+    /// The license is a template whose copyright line is a placeholder, and there
+    /// is no copyright line to fill it in with.
+    Placeholder { path: PathBuf, line: String },
+    /// The config file could not be parsed, or has invalid settings.
+    Config { path: PathBuf, reason: String },
+    /// The glob pattern used to walk a directory was invalid or could not be read.
+    Glob { pattern: String, reason: String },
+    /// Reading a file failed.
+    Read { path: PathBuf, source: io::Error },
+    /// Writing a file, or creating its directory, failed.
+    Write { path: PathBuf, source: io::Error },
+    /// A git hook that was not installed by docstring-rs is in the way.
+    HookExists { path: PathBuf },
+    /// The directory could not be watched for changes.
+    Watch { path: PathBuf, reason: String },
+    /// Running the git `command`, e.g. `diff`, failed, for instance because the
+    /// directory is not in a git repository.
+    Git {
+        path: PathBuf,
+        command: String,
+        reason: String,
+    },
+}
+
+impl Error {
+    /// The path that the error concerns, if any.
+    pub fn path(&self) -> Option<&Path> {
+        let path = match self {
+            Error::LicenseNotFound { path, .. }
+            | Error::UnsupportedFileType { path }
+            | Error::UnsupportedLanguage { path, .. }
+            | Error::MalformedHeader { path, .. }
+            | Error::Decode { path }
+            | Error::Placeholder { path, .. }
+            | Error::Config { path, .. }
+            | Error::Read { path, .. }
+            | Error::Write { path, .. }
+            | Error::HookExists { path }
+            | Error::Watch { path, .. }
+            | Error::Git { path, .. } => path,
+            Error::Glob { .. } => return None,
+        };
+
+        match path.as_os_str().is_empty() {
+            true => None,
+            false => Some(path),
+        }
+    }
+
+    /// A suggestion for the user on how to resolve the error.
+    pub fn hint(&self) -> String {
+        match self {
+            Error::LicenseNotFound { .. } => {
+                "pass the path to your LICENSE file with `--license <LICENSE>`".to_owned()
+            }
+            Error::UnsupportedFileType { .. } => {
+                "check that the file ending belongs to a supported language".to_owned()
+            }
+            Error::UnsupportedLanguage { .. } => {
+                "pin the file to a supported language in the config file if it was recognised wrongly"
+                    .to_owned()
+            }
+            Error::MalformedHeader { .. } => {
+                "fix or remove the existing docstring by hand and run again".to_owned()
+            }
+            Error::Decode { .. } => {
+                "only UTF-8 encoded text files can be given docstrings".to_owned()
+            }
+            Error::Placeholder { .. } => {
+                "pass a LICENSE file with the copyright line filled in, e.g. `Copyright (c) 2024 Jane Doe`, instead of an SPDX id"
+                    .to_owned()
+            }
+            Error::Config { .. } => {
+                "check the config file against the examples in the README".to_owned()
+            }
+            Error::Glob { .. } => {
+                "check that the directory exists and that its name has no glob characters"
+                    .to_owned()
+            }
+            Error::Read { source, .. } | Error::Write { source, .. } => match source.kind() {
+                io::ErrorKind::NotFound => "check that the path exists".to_owned(),
+                io::ErrorKind::PermissionDenied => {
+                    "check the permissions of the file and its directory".to_owned()
+                }
+                _ => format!("the operating system reported: {}", source),
+            },
+            Error::HookExists { .. } => {
+                "move the existing hook out of the way, or pass `--overwrite` to replace it"
+                    .to_owned()
+            }
+            Error::Watch { .. } => {
+                "check that the directory exists and that the system limit on watched files is high enough"
+                    .to_owned()
+            }
+            Error::Git { command, .. } => match command.as_str() {
+                "diff" => {
+                    "check that the revision given to `--since` exists, or pass the files to process as arguments"
+                        .to_owned()
+                }
+                "log" | "blame" => {
+                    "check that the history of the file can be read, or remove the `[authors]` table from the config file"
+                        .to_owned()
+                }
+                "show" | "add" => {
+                    "check that no other git command is running on the repository and try again"
+                        .to_owned()
+                }
+                _ => "run inside a git repository, or pass the files to process as arguments"
+                    .to_owned(),
+            },
+        }
+    }
+
+    /// Set the path of an error that was created for in-memory contents.
+    pub(crate) fn with_path(mut self, new_path: &Path) -> Self {
+        match &mut self {
+            Error::LicenseNotFound { path, .. }
+            | Error::UnsupportedFileType { path }
+            | Error::UnsupportedLanguage { path, .. }
+            | Error::MalformedHeader { path, .. }
+            | Error::Decode { path }
+            | Error::Placeholder { path, .. }
+            | Error::Config { path, .. }
+            | Error::Read { path, .. }
+            | Error::Write { path, .. }
+            | Error::HookExists { path }
+            | Error::Watch { path, .. }
+            | Error::Git { path, .. } => {
+                if path.as_os_str().is_empty() {
+                    *path = new_path.to_path_buf();
+                }
+            }
+            Error::Glob { .. } => (),
+        };
+
+        self
+    }
+
+    /// Render the error as a multiline diagnostic for the terminal.
+    pub fn diagnostic(&self) -> String {
+        format!("error: {}\n  hint: {}", self, self.hint())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::LicenseNotFound { path, source } => {
+                write!(f, "could not read LICENSE `{}`: {}", path.display(), source)
+            }
+            Error::UnsupportedFileType { path } => {
+                write!(f, "no matching filetype for `{}`", path.display())
+            }
+            Error::UnsupportedLanguage { path, language } => match path.as_os_str().is_empty() {
+                true => write!(f, "contents are written in unsupported {}", language),
+                false => write!(
+                    f,
+                    "`{}` is written in unsupported {}",
+                    path.display(),
+                    language
+                ),
+            },
+            Error::MalformedHeader { path, reason } => match path.as_os_str().is_empty() {
+                true => write!(f, "malformed docstring: {}", reason),
+                false => write!(f, "malformed docstring in `{}`: {}", path.display(), reason),
+            },
+            Error::Decode { path } => match path.as_os_str().is_empty() {
+                true => write!(f, "contents are not valid UTF-8"),
+                false => write!(f, "`{}` is not valid UTF-8", path.display()),
+            },
+            Error::Placeholder { path, line } => match path.as_os_str().is_empty() {
+                true => write!(f, "the license still has the placeholder `{}`", line),
+                false => write!(
+                    f,
+                    "the license still has the placeholder `{}`, and `{}` has no copyright line to fill it in with",
+                    line,
+                    path.display()
+                ),
+            },
+            Error::Config { path, reason } => match path.as_os_str().is_empty() {
+                true => write!(f, "invalid config: {}", reason),
+                false => write!(f, "invalid config `{}`: {}", path.display(), reason),
+            },
+            Error::Glob { pattern, reason } => {
+                write!(f, "could not glob `{}`: {}", pattern, reason)
+            }
+            Error::Read { path, source } => {
+                write!(f, "could not read `{}`: {}", path.display(), source)
+            }
+            Error::Write { path, source } => {
+                write!(f, "could not write `{}`: {}", path.display(), source)
+            }
+            Error::HookExists { path } => {
+                write!(
+                    f,
+                    "a hook not installed by docstring-rs already exists at `{}`",
+                    path.display()
+                )
+            }
+            Error::Watch { path, reason } => {
+                write!(f, "could not watch `{}`: {}", path.display(), reason)
+            }
+            Error::Git {
+                path,
+                command,
+                reason,
+            } => {
+                write!(f, "git {} failed in `{}`: {}", command, path.display(), reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::LicenseNotFound { source, .. }
+            | Error::Read { source, .. }
+            | Error::Write { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_error {
+    use super::*;
+
+    #[test]
+    fn diagnostic_with_path() {
+        let e = Error::UnsupportedFileType {
+            path: PathBuf::from("src/lol.kebab"),
+        };
+        assert_eq!(e.path(), Some(Path::new("src/lol.kebab")));
+        let d = e.diagnostic();
+        assert!(d.starts_with("error: no matching filetype for `src/lol.kebab`"));
+        assert!(d.contains("\n  hint: "));
+    }
+
+    #[test]
+    fn with_path_fills_empty_path() {
+        let e = Error::Decode {
+            path: PathBuf::new(),
+        };
+        assert_eq!(e.path(), None);
+        let e = e.with_path(Path::new("src/main.rs"));
+        assert_eq!(e.path(), Some(Path::new("src/main.rs")));
+        assert_eq!(e.to_string(), "`src/main.rs` is not valid UTF-8");
+    }
+
+    #[test]
+    fn hint_from_io_kind() {
+        let e = Error::Write {
+            path: PathBuf::from("src"),
+            source: io::Error::new(io::ErrorKind::PermissionDenied, "lol"),
+        };
+        assert!(e.hint().contains("permissions"));
+        let e = Error::Write {
+            path: PathBuf::from("src/main.rs"),
+            source: io::Error::new(io::ErrorKind::AlreadyExists, "lol"),
+        };
+        assert!(!e.hint().contains("--overwrite"));
+        let e = Error::HookExists {
+            path: PathBuf::from(".git/hooks/pre-commit"),
+        };
+        assert!(e.hint().contains("--overwrite"));
+    }
+
+    #[test]
+    fn hint_from_git_command() {
+        let git = |command: &str| Error::Git {
+            path: PathBuf::from("."),
+            command: command.to_owned(),
+            reason: "fatal: lol".to_owned(),
+        };
+        assert!(git("diff").hint().contains("--since"));
+        assert!(git("blame").hint().contains("[authors]"));
+        assert!(!git("rev-parse").hint().contains("--since"));
+        assert!(!git("add").hint().contains("--since"));
+        assert_eq!(
+            git("rev-parse").to_string(),
+            "git rev-parse failed in `.`: fatal: lol"
+        );
+    }
+}
@@ -22,196 +22,168 @@
 * SOFTWARE.
 *
 * File created: 2023-10-01
-* Last updated: 2023-10-04
+* Last updated: 2026-10-18
 */
 
 use std::collections::HashMap;
 use std::fmt;
-use std::io;
+use std::path::{Path, PathBuf};
 
 use once_cell::sync::Lazy;
 
-use crate::*;
+use crate::comment::Style;
+use crate::language::{self, Language, LanguageId};
+use crate::{CommentStyle, Error, Result};
 
-///
+/// Lookup table from the file ending of a built-in language to its `FileType`.
 static FILE2TYPE: Lazy<HashMap<&'static str, FileType>> = Lazy::new(|| {
     let mut m = HashMap::new();
-    m.insert("c", FileType::C);
-
-    m.insert("cc", FileType::CPP);
-    m.insert("cpp", FileType::CPP);
-    m.insert("cxx", FileType::CPP);
-
-    m.insert("cs", FileType::CSharp);
-
-    m.insert("pyx", FileType::Cython);
-
-    m.insert("ex", FileType::Elixir);
-    m.insert("exs", FileType::Elixir);
-
-    m.insert("erl", FileType::Erlang);
-    m.insert("hrl", FileType::Erlang);
-
-    m.insert("fs", FileType::FSharp);
-    m.insert("fsi", FileType::FSharp);
-    m.insert("fsx", FileType::FSharp);
-    m.insert("fsscript", FileType::FSharp);
-
-    m.insert("go", FileType::Go);
-
-    m.insert("hs", FileType::Haskell);
-    m.insert("lhs", FileType::Haskell);
-
-    // RIP Terry A. Davis
-    m.insert("HC", FileType::HolyC);
-
-    m.insert("java", FileType::Java);
-
-    m.insert("js", FileType::JavaScript);
-
-    m.insert("jl", FileType::Julia);
-
-    m.insert("kt", FileType::Kotlin);
-    m.insert("kts", FileType::Kotlin);
-
-    m.insert("lisp", FileType::Lisp);
-    m.insert("lsp", FileType::Lisp);
-    m.insert("l", FileType::Lisp);
-    m.insert("cl", FileType::Lisp);
-    m.insert("fasl", FileType::Lisp);
-
-    m.insert("lua", FileType::Lua);
-
-    m.insert("plx", FileType::Perl);
-    m.insert("pm", FileType::Perl);
-    m.insert("xs", FileType::Perl);
-    m.insert("t", FileType::Perl);
-    m.insert("pod", FileType::Perl);
-    m.insert("cgi", FileType::Perl);
-
-    m.insert("php", FileType::PHP);
-    m.insert("phar", FileType::PHP);
-    m.insert("phtml", FileType::PHP);
-    m.insert("pht", FileType::PHP);
-    m.insert("phps", FileType::PHP);
-
-    m.insert("ps1", FileType::PowerShell);
-    m.insert("psc1", FileType::PowerShell);
-    m.insert("pssc", FileType::PowerShell);
-
-    m.insert("pl", FileType::Prolog);
-    m.insert("pro", FileType::Prolog);
-    m.insert("P", FileType::Prolog);
-
-    m.insert("py", FileType::Python);
-    m.insert("pyi", FileType::Python);
-    m.insert("pyc", FileType::Python);
-    m.insert("pyd", FileType::Python);
-    m.insert("pyw", FileType::Python);
-    m.insert("pyz", FileType::Python);
-
-    m.insert("qs", FileType::QSharp);
-
-    m.insert("r", FileType::R);
-    m.insert("rdata", FileType::R);
-    m.insert("rds", FileType::R);
-
-    m.insert("rb", FileType::Ruby);
-
-    m.insert("rs", FileType::Rust);
-
-    m.insert("scala", FileType::Scala);
-    m.insert("sc", FileType::Scala);
-
-    m.insert("swift", FileType::Swift);
-    m.insert("SWIFT", FileType::Swift);
-
-    m.insert("ts", FileType::TypeScript);
-    m.insert("tsx", FileType::TypeScript);
-    m.insert("mts", FileType::TypeScript);
-    m.insert("cts", FileType::TypeScript);
-
-    m.insert("vim", FileType::Vim);
-
-    m.insert("zig", FileType::Zig);
-    m.insert("zir", FileType::Zig);
+    for ft in ALL {
+        for e in ft.file_endings() {
+            m.insert(e, *ft);
+        }
+    }
+    m
+});
 
+/// Lookup table from full file name to its `FileType`, for files that are
+/// recognised by their name rather than their file ending.
+static FILENAME2TYPE: Lazy<HashMap<&'static str, FileType>> = Lazy::new(|| {
+    let mut m = HashMap::new();
+    for ft in ALL {
+        for n in ft.file_names() {
+            m.insert(n, *ft);
+        }
+    }
     m
 });
 
-#[allow(dead_code)]
-///
-static TYPE2STYLE: Lazy<HashMap<FileType, CommentStyle>> = Lazy::new(|| {
+/// Lookup table from the file ending of a compiled or archived artifact to the
+/// `FileType` it was built from. Artifacts are never given docstrings.
+static ARTIFACT2TYPE: Lazy<HashMap<&'static str, FileType>> = Lazy::new(|| {
     let mut m = HashMap::new();
-    m.insert(FileType::C, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(FileType::CPP, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(FileType::CSharp, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(FileType::Cython, CommentStyle::new("\"\"\"", "", "\"\"\""));
-    m.insert(FileType::Elixir, CommentStyle::new("# ", "# ", "# "));
-    m.insert(FileType::Erlang, CommentStyle::new("%", "% ", "%"));
-    m.insert(FileType::FSharp, CommentStyle::new("(*", "* ", "*)"));
-    m.insert(FileType::Go, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(FileType::Haskell, CommentStyle::new("{-", "- ", "-}"));
-    m.insert(FileType::HolyC, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(FileType::Java, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(FileType::JavaScript, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(FileType::Julia, CommentStyle::new("#=", "= ", "=#"));
-    m.insert(FileType::Kotlin, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(FileType::Lisp, CommentStyle::new(";;;;", ";;;; ", ";;;;"));
-    m.insert(FileType::Lua, CommentStyle::new("--[[", "-- ", "--]]"));
-    m.insert(FileType::Perl, CommentStyle::new("=", "", "=cut"));
-    m.insert(FileType::PHP, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(FileType::Prolog, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(FileType::Python, CommentStyle::new("\"\"\"", "", "\"\"\""));
-    m.insert(FileType::QSharp, CommentStyle::new("///", "///", "///"));
-    m.insert(FileType::R, CommentStyle::new("#", "# ", "#"));
-    m.insert(FileType::Ruby, CommentStyle::new("=begin", "", "=end"));
-    m.insert(FileType::Rust, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(FileType::Scala, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(FileType::Swift, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(FileType::TypeScript, CommentStyle::new("/*", "* ", "*/"));
-    m.insert(
-        FileType::Vim,
-        CommentStyle::new("\'\"\'", "\'\"\'", "\'\"\'"),
-    );
-    m.insert(FileType::Zig, CommentStyle::new("/*", "* ", "*/"));
+    for ft in ALL {
+        for e in ft.artifact_endings() {
+            m.insert(e, *ft);
+        }
+    }
     m
 });
 
-///
+/// Every `FileType`, in alphabetical order.
+static ALL: &[FileType] = &[
+    FileType::C,
+    FileType::CMake,
+    FileType::Config,
+    FileType::CPP,
+    FileType::CSharp,
+    FileType::CSS,
+    FileType::Cython,
+    FileType::Dockerfile,
+    FileType::Elixir,
+    FileType::Erlang,
+    FileType::FSharp,
+    FileType::Go,
+    FileType::GraphQL,
+    FileType::Haskell,
+    FileType::HCL,
+    FileType::HolyC,
+    FileType::HTML,
+    FileType::Java,
+    FileType::JavaScript,
+    FileType::Julia,
+    FileType::Kotlin,
+    FileType::LESS,
+    FileType::Lisp,
+    FileType::Lua,
+    FileType::Makefile,
+    FileType::Markdown,
+    FileType::Nix,
+    FileType::Perl,
+    FileType::PHP,
+    FileType::PowerShell,
+    FileType::Prolog,
+    FileType::Protobuf,
+    FileType::Python,
+    FileType::QSharp,
+    FileType::R,
+    FileType::Ruby,
+    FileType::Rust,
+    FileType::Scala,
+    FileType::SCSS,
+    FileType::Shell,
+    FileType::SQL,
+    FileType::Starlark,
+    FileType::Svelte,
+    FileType::Swift,
+    FileType::Thrift,
+    FileType::TOML,
+    FileType::TypeScript,
+    FileType::Vim,
+    FileType::Vue,
+    FileType::XML,
+    FileType::YAML,
+    FileType::Zig,
+];
+
+/// All programming languages that docstrings can be generated for.
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum FileType {
     C,
+    CMake,
+    Config,
     CPP,
     CSharp,
+    CSS,
     Cython,
+    Dockerfile,
     Elixir,
     Erlang,
     FSharp,
     Go,
+    GraphQL,
     Haskell,
+    HCL,
     HolyC,
+    HTML,
     Java,
     JavaScript,
     Julia,
     Kotlin,
+    LESS,
     Lisp,
     Lua,
+    Makefile,
+    Markdown,
+    Nix,
     Perl,
     PHP,
     PowerShell,
     Prolog,
+    Protobuf,
     Python,
     QSharp,
     R,
     Ruby,
     Rust,
     Scala,
+    SCSS,
+    Shell,
+    SQL,
+    Starlark,
+    Svelte,
     Swift,
+    Thrift,
+    TOML,
     TypeScript,
     Vim,
+    Vue,
+    XML,
+    YAML,
     Zig,
+    /// A language registered at runtime, e.g. from the config file.
+    Custom(LanguageId),
 }
 
 ///
@@ -224,153 +196,310 @@ pub enum FileType {
 ///
 impl fmt::Display for FileType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use filetype::FileType::*;
-        match self {
-            C => write!(f, "C"),
-            CPP => write!(f, "C++"),
-            CSharp => write!(f, "C#"),
-            Cython => write!(f, "Cython"),
-            Elixir => write!(f, "Elixir"),
-            Erlang => write!(f, "Erlang"),
-            FSharp => write!(f, "FSharp"),
-            Go => write!(f, "Go"),
-            Haskell => write!(f, "Haskell"),
-            HolyC => write!(f, "HolyC"),
-            Java => write!(f, "Java"),
-            JavaScript => write!(f, "JavaScript"),
-            Julia => write!(f, "Julia"),
-            Kotlin => write!(f, "Kotlin"),
-            Lisp => write!(f, "Lisp"),
-            Lua => write!(f, "Lua"),
-            Perl => write!(f, "Perl"),
-            PHP => write!(f, "PHP"),
-            PowerShell => write!(f, "PowerShell"),
-            Prolog => write!(f, "Prolog"),
-            Python => write!(f, "Python"),
-            QSharp => write!(f, "QSharp"),
-            R => write!(f, "R"),
-            Ruby => write!(f, "Ruby"),
-            Rust => write!(f, "Rust"),
-            Scala => write!(f, "Scala"),
-            Swift => write!(f, "Swift"),
-            TypeScript => write!(f, "TypeScript"),
-            Vim => write!(f, "Vim"),
-            Zig => write!(f, "Zig"),
-        }
+        write!(f, "{}", self.language().name())
     }
 }
 
-///
 impl FileType {
-    ///
-    pub fn file_endings(&self) -> Vec<&str> {
-        use filetype::FileType::*;
-        match self {
-            C => vec!["c"],
-            CPP => vec!["cc", "cpp", "cxx"],
-            CSharp => vec!["cs"],
-            Cython => vec!["pyx"],
-            Elixir => vec!["ex", "exs"],
-            Erlang => vec!["erl", "hrl"],
-            FSharp => vec!["fs", "fsi", "fsx", "fsscript"],
-            Go => vec!["go"],
-            Haskell => vec!["hs", "lhs"],
-            HolyC => vec!["HC"],
-            Java => vec!["java"],
-            JavaScript => vec!["js"],
-            Julia => vec!["jl"],
-            Kotlin => vec!["kt", "kts"],
-            Lisp => vec!["lisp", "lsp", "l", "cl", "fasl"],
-            Lua => vec!["lua"],
-            Perl => vec!["plx", "pm", "xs", "t", "pod", "cgi"],
-            PHP => vec!["php", "phar", "phtml", "pht", "phps"],
-            PowerShell => vec!["ps1", "psc1", "pssc"],
-            Prolog => vec!["pl", "pro", "P"],
-            Python => vec!["py", "pyi", "pyc", "pyd", "pyw", "pyz"],
-            QSharp => vec!["qs"],
-            R => vec!["r", "rdata", "rds"],
-            Ruby => vec!["rb"],
-            Rust => vec!["rs"],
-            Scala => vec!["scala", "sc"],
-            Swift => vec!["swift", "SWIFT"],
-            TypeScript => vec!["ts", "tsx", "mts", "cts"],
-            Vim => vec!["vim"],
-            Zig => vec!["zig", "zir"],
-        }
+    /// Every built-in `FileType`.
+    pub fn all() -> &'static [FileType] {
+        ALL
+    }
+
+    /// Every built-in `FileType`, followed by the languages registered at runtime.
+    pub fn registry() -> Vec<FileType> {
+        let mut all = ALL.to_vec();
+        all.extend(language::registered());
+        all
     }
 
-    ///
-    pub fn try_from_filename(fname: &str) -> Result<FileType, io::Error> {
-        let fe: &str = match fname.split('.').last() {
+    /// The definition of the language of the file type.
+    pub fn language(&self) -> &'static Language {
+        language::language(*self)
+    }
+
+    /// Whether or not the file type is a language registered at runtime.
+    pub fn is_custom(&self) -> bool {
+        matches!(self, FileType::Custom(_))
+    }
+
+    /// All file endings that belong to the file type.
+    pub fn file_endings(&self) -> Vec<&'static str> {
+        self.language().endings().to_vec()
+    }
+
+    /// The file endings of binary artifacts built from the file type, e.g. `pyc`,
+    /// which are skipped instead of given docstrings.
+    pub fn artifact_endings(&self) -> Vec<&'static str> {
+        self.language().artifacts().to_vec()
+    }
+
+    /// The `FileType` that the artifact `fname` was built from, if it has the file
+    /// ending of an artifact, ignoring case, e.g. `module.pyc` or `.RData`.
+    pub fn artifact_of(fname: &str) -> Option<FileType> {
+        let name = Path::new(fname).file_name()?.to_str()?;
+        let (_, ending) = name.rsplit_once('.')?;
+        let ending = ending.to_ascii_lowercase();
+        language::registered()
+            .into_iter()
+            .find(|ft| ft.artifact_endings().contains(&ending.as_str()))
+            .or_else(|| ARTIFACT2TYPE.get(ending.as_str()).copied())
+    }
+
+    /// All full file names that belong to the file type, e.g. `Makefile`.
+    pub fn file_names(&self) -> Vec<&'static str> {
+        self.language().file_names().to_vec()
+    }
+
+    /// Try and find the `FileType` of a file from its full file name, or otherwise
+    /// from its file ending. Languages registered at runtime take precedence over
+    /// the built-in ones.
+    pub fn try_from_filename(fname: &str) -> Result<FileType> {
+        let name: &str = Path::new(fname)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(fname);
+        let custom = language::registered();
+        if let Some(ft) = custom.iter().find(|ft| ft.file_names().contains(&name)) {
+            return Ok(*ft);
+        }
+        if let Some(ft) = FILENAME2TYPE.get(name) {
+            return Ok(*ft);
+        }
+
+        let fe: &str = match name.split('.').next_back() {
             Some(e) => e,
             None => {
-                return Err(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    "could not find a file ending",
-                ));
+                return Err(Error::UnsupportedFileType {
+                    path: PathBuf::from(fname),
+                })
             }
         };
 
+        if let Some(ft) = custom.iter().find(|ft| ft.file_endings().contains(&fe)) {
+            return Ok(*ft);
+        }
         match FILE2TYPE.get(fe) {
             Some(ft) => Ok(*ft),
-            None => Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                "no matching filetype",
-            )),
+            None => Err(Error::UnsupportedFileType {
+                path: PathBuf::from(fname),
+            }),
+        }
+    }
+
+    /// Try and find the `FileType` from its name or one of its aliases, e.g. `Rust`,
+    /// `C++` or `shell-script`, ignoring case. Falls back to treating `name` as a
+    /// file ending, e.g. `rs`.
+    pub fn try_from_name(name: &str) -> Result<FileType> {
+        let is_called = |ft: &FileType| {
+            let language = ft.language();
+            std::iter::once(language.name())
+                .chain(language.aliases().iter().copied())
+                .any(|n| n.eq_ignore_ascii_case(name))
+        };
+        match FileType::registry().into_iter().find(is_called) {
+            Some(ft) => Ok(ft),
+            None => FileType::try_from_filename(name),
         }
     }
+
+    /// The `FileType` of scripts run by `interpreter`, e.g. `perl`, if any.
+    pub fn from_interpreter(interpreter: &str) -> Option<FileType> {
+        let custom = language::registered();
+        custom
+            .iter()
+            .chain(ALL)
+            .find(|ft| ft.language().interpreters().contains(&interpreter))
+            .copied()
+    }
 }
 
-///
 impl FileType {
-    pub fn get_comment_style(&self) -> CommentStyle {
-        use filetype::FileType::*;
-        let (start, normal, end) = match self {
-            C => ("/*", "* ", "*/"),
-            CPP => ("/*", "* ", "*/"),
-            CSharp => ("/*", "* ", "*/"),
-            Cython => ("\"\"\"", "", "\"\"\""),
-            Elixir => ("# ", "# ", "# "),
-            Erlang => ("%", "% ", "%"),
-            FSharp => ("(*", "* ", "*)"),
-            Go => ("/*", "* ", "*/"),
-            Haskell => ("{-", "- ", "-}"),
-            HolyC => ("/*", "* ", "*/"),
-            Java => ("/*", "* ", "*/"),
-            JavaScript => ("/*", "* ", "*/"),
-            Julia => ("#=", "= ", "=#"),
-            Kotlin => ("/*", "* ", "*/"),
-            Lisp => (";;;;", ";;;; ", ";;;;"),
-            Lua => ("--[[", "-- ", "--]]"),
-            Perl => ("=", "", "=cut"),
-            PHP => ("/*", "* ", "*/"),
-            PowerShell => ("<#", "# ", "#>"),
-            Prolog => ("/*", "* ", "*/"),
-            Python => ("\"\"\"", "", "\"\"\""),
-            QSharp => ("///", "///", "///"),
-            R => ("#", "# ", "#"),
-            Ruby => ("=begin", "", "=end"),
-            Rust => ("/*", "* ", "*/"),
-            Scala => ("/*", "* ", "*/"),
-            Swift => ("/*", "* ", "*/"),
-            TypeScript => ("/*", "* ", "*/"),
-            Vim => ("\'\"\'", "\'\"\'", "\'\"\'"),
-            Zig => ("/*", "* ", "*/"),
-        };
+    /// The `CommentStyle` used when writing docstrings for the file type.
+    pub fn get_comment_style(&self) -> CommentStyle<'static> {
+        self.comment_style(self.default_style())
+    }
+
+    /// The style docstrings of the file type are written in unless chosen otherwise.
+    pub fn default_style(&self) -> Style {
+        self.language().style()
+    }
 
-        CommentStyle::new(start, normal, end)
+    /// The `CommentStyle` for writing docstrings of the file type in `style`. File
+    /// types without line comments always use their block style.
+    pub fn comment_style(&self, style: Style) -> CommentStyle<'static> {
+        match (style, self.line_comment()) {
+            (Style::Line, Some(prefix)) => CommentStyle::line(prefix),
+            _ => self.language().block_comment(),
+        }
+    }
+
+    /// All `CommentStyle`s that docstrings of the file type may be written in, with
+    /// the default style first.
+    pub fn comment_styles(&self) -> Vec<CommentStyle<'static>> {
+        let mut styles = vec![self.get_comment_style()];
+        for style in [Style::Block, Style::Line] {
+            let cs = self.comment_style(style);
+            if !styles.contains(&cs) {
+                styles.push(cs);
+            }
+        }
+        styles
+    }
+
+    /// The prefix of a line comment of the file type, e.g. `// `, if it has any.
+    pub fn line_comment(&self) -> Option<&'static str> {
+        self.language().line_comment()
+    }
+
+    /// The length in bytes of the lines at the top of `contents` that must stay
+    /// before the docstring: a shebang, the lines matching the preamble of the
+    /// language, such as the encoding declaration of Python, and the XML
+    /// declaration and doctype of markup and the front matter of Markdown.
+    /// An inner attribute of Rust, `#![...]`, is not a shebang. A leading UTF-8
+    /// byte order mark is part of the preamble.
+    pub fn preamble_len(&self, contents: &str) -> usize {
+        use FileType::*;
+        let mut len = bom_len(contents);
+        if contents[len..].starts_with("#!") && !contents[len..].starts_with("#![") {
+            len = line_end(contents, len);
+        }
+
+        let preamble = self.language().preamble();
+        while len < contents.len() && preamble.iter().any(|p| p.is_match(&contents[len..])) {
+            len = line_end(contents, len);
+        }
+
+        match self {
+            HTML | XML => {
+                if let Some(end) = declaration_end(contents, len, "<?xml", "?>") {
+                    len = end;
+                }
+                let ws = contents[len..].len() - contents[len..].trim_start().len();
+                if let Some(end) = declaration_end(contents, len + ws, "<!DOCTYPE", ">") {
+                    len = end;
+                }
+            }
+            Markdown
+                if contents[len..].starts_with("---\n")
+                    || contents[len..].starts_with("---\r\n") =>
+            {
+                let mut end = line_end(contents, len);
+                while end < contents.len() {
+                    let next = line_end(contents, end);
+                    if contents[end..next].trim_end() == "---" {
+                        len = next;
+                        break;
+                    }
+                    end = next;
+                }
+            }
+            _ => {}
+        }
+        len
+    }
+}
+
+impl FileType {
+    /// The byte offsets in `contents` at which an existing docstring may start.
+    /// Docstrings are written right after the preamble, but in Rust a docstring
+    /// may also follow the inner docs, `//!`, and inner attributes, `#![...]`, of
+    /// the module, since comments do not affect either.
+    pub fn header_offsets(&self, contents: &str) -> Vec<usize> {
+        let from = self.preamble_len(contents);
+        let mut offsets = vec![from];
+        if *self == FileType::Rust {
+            let inner = from + rust_inner_len(&contents[from..]);
+            if inner != from {
+                offsets.push(inner);
+            }
+        }
+        offsets
+    }
+}
+
+/// The length in bytes of the inner docs and inner attributes at the top of a
+/// Rust module, including blank lines between them.
+fn rust_inner_len(contents: &str) -> usize {
+    let mut len = 0;
+    let mut depth = 0usize;
+    let mut in_block_doc = false;
+    let mut end = 0;
+    for line in contents.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if in_block_doc {
+            in_block_doc = !trimmed.contains("*/");
+        } else if depth > 0 {
+            depth = bracket_depth(depth, trimmed);
+        } else if let Some(doc) = trimmed.strip_prefix("/*!") {
+            in_block_doc = !doc.contains("*/");
+        } else if trimmed.starts_with("#![") {
+            depth = bracket_depth(0, trimmed);
+        } else if !trimmed.starts_with("//!") && !trimmed.is_empty() {
+            break;
+        }
+        len += line.len();
+        if !trimmed.is_empty() && !in_block_doc && depth == 0 {
+            end = len;
+        }
+    }
+    end
+}
+
+/// The nesting depth of square brackets after `line`, starting at `depth`.
+fn bracket_depth(depth: usize, line: &str) -> usize {
+    line.chars().fold(depth, |d, c| match c {
+        '[' => d + 1,
+        ']' => d.saturating_sub(1),
+        _ => d,
+    })
+}
+
+/// The length in bytes of the UTF-8 byte order mark `contents` starts with, if any.
+pub(crate) fn bom_len(contents: &str) -> usize {
+    match contents.starts_with('\u{feff}') {
+        true => '\u{feff}'.len_utf8(),
+        false => 0,
+    }
+}
+
+/// The byte offset just past the line of `contents` starting at `from`.
+fn line_end(contents: &str, from: usize) -> usize {
+    match contents[from..].find('\n') {
+        Some(i) => from + i + 1,
+        None => contents.len(),
+    }
+}
+
+/// The byte offset just past the markup declaration opened by `open` at `from` in
+/// `contents`, and past the rest of its line if that is only whitespace. The
+/// opening marker is matched ignoring case, e.g. `<!doctype html>`.
+fn declaration_end(contents: &str, from: usize, open: &str, close: &str) -> Option<usize> {
+    let rest = &contents[from..];
+    if !rest
+        .get(..open.len())
+        .is_some_and(|s| s.eq_ignore_ascii_case(open))
+    {
+        return None;
+    }
+
+    let end = from + rest.find(close)? + close.len();
+    let line = line_end(contents, end);
+    match contents[end..line].trim().is_empty() {
+        true => Some(line),
+        false => Some(end),
     }
 }
 
 #[cfg(test)]
 mod tests_filetype {
-    use super::{io, FileType, FILE2TYPE, TYPE2STYLE};
+    use super::{CommentStyle, Error, FileType, Language, Style, ALL, FILE2TYPE, FILENAME2TYPE};
+    use regex::Regex;
 
     #[test]
     fn try_from_filename_error() {
-        let expected = Err(io::ErrorKind::NotFound);
-        let result = FileType::try_from_filename("_lol__haha.kebab").map_err(|e| e.kind());
-        assert_eq!(expected, result);
+        let result = FileType::try_from_filename("_lol__haha.kebab");
+        assert!(matches!(result, Err(Error::UnsupportedFileType { .. })));
     }
 
     #[test]
@@ -383,11 +512,170 @@ mod tests_filetype {
     }
 
     #[test]
-    fn get_comment_style_ok_all() {
-        for filetype in TYPE2STYLE.keys() {
-            let expected = TYPE2STYLE.get(filetype).unwrap();
-            let result = filetype.get_comment_style();
-            assert_eq!(expected, &result);
+    fn try_from_filename_full_name() {
+        for (file_name, expected) in FILENAME2TYPE.iter() {
+            let path = format!("some/dir/{}", file_name);
+            assert_eq!(&FileType::try_from_filename(&path).unwrap(), expected);
+            assert!(expected.file_names().contains(file_name));
         }
+        assert_eq!(
+            FileType::try_from_filename("some.dir/main.rs").unwrap(),
+            FileType::Rust
+        );
+    }
+
+    #[test]
+    fn preamble_len_ok() {
+        assert_eq!(FileType::Rust.preamble_len("fn main() {}\n"), 0);
+        assert_eq!(FileType::Shell.preamble_len("#!/bin/sh\necho hi\n"), 10);
+        assert_eq!(FileType::Shell.preamble_len("#!/bin/sh"), 9);
+
+        let dockerfile = "# syntax=docker/dockerfile:1\n# escape=`\n\nFROM alpine\n";
+        assert_eq!(FileType::Dockerfile.preamble_len(dockerfile), 40);
+        assert_eq!(FileType::Shell.preamble_len(dockerfile), 0);
+
+        let svg = "<?xml version=\"1.0\"?>\n<!DOCTYPE svg>\n<svg/>\n";
+        assert_eq!(FileType::XML.preamble_len(svg), 37);
+        let html = "<!doctype html><html></html>\n";
+        assert_eq!(FileType::HTML.preamble_len(html), 15);
+
+        let markdown = "---\ntitle: Hello\n---\n# Hello\n";
+        assert_eq!(FileType::Markdown.preamble_len(markdown), 21);
+        assert_eq!(FileType::Markdown.preamble_len("---\nunclosed\n"), 0);
+
+        let css = "@charset \"UTF-8\";\nbody {}\n";
+        assert_eq!(FileType::CSS.preamble_len(css), 18);
+    }
+
+    #[test]
+    fn preamble_len_bom() {
+        assert_eq!(FileType::Rust.preamble_len("\u{feff}fn main() {}\n"), 3);
+        assert_eq!(
+            FileType::Shell.preamble_len("\u{feff}#!/bin/sh\necho hi\n"),
+            13
+        );
+        let svg = "\u{feff}<?xml version=\"1.0\"?>\n<!DOCTYPE svg>\n<svg/>\n";
+        assert_eq!(FileType::XML.preamble_len(svg), 40);
+        let markdown = "\u{feff}---\ntitle: Hello\n---\n# Hello\n";
+        assert_eq!(FileType::Markdown.preamble_len(markdown), 24);
+    }
+
+    #[test]
+    fn preamble_len_python_and_rust() {
+        let python = "#!/usr/bin/env python\n# -*- coding: utf-8 -*-\n\"\"\"Docs.\"\"\"\n";
+        assert_eq!(FileType::Python.preamble_len(python), 46);
+        assert_eq!(
+            FileType::Python.preamble_len("# coding=latin-1\nx = 1\n"),
+            17
+        );
+        assert_eq!(FileType::Python.preamble_len("# A comment\nx = 1\n"), 0);
+
+        assert_eq!(FileType::Rust.preamble_len("#![allow(dead_code)]\n"), 0);
+
+        let ruby = "# frozen_string_literal: true\n# -*- coding: utf-8 -*-\n# Docs\n";
+        assert_eq!(FileType::Ruby.preamble_len(ruby), 54);
+    }
+
+    #[test]
+    fn header_offsets_rust() {
+        let rust = "//! Crate docs.\n\n#![cfg_attr(\n    docsrs,\n    feature(doc_cfg)\n)]\n/*\n";
+        assert_eq!(FileType::Rust.header_offsets(rust), vec![0, 66]);
+        assert_eq!(FileType::Rust.header_offsets("fn main() {}\n"), vec![0]);
+        assert_eq!(FileType::Go.header_offsets("//! Not Rust.\n"), vec![0]);
+    }
+
+    #[test]
+    fn artifacts_are_not_sources() {
+        for ending in ["pyc", "pyd", "pyz", "rdata", "rds", "fasl", "phar"] {
+            let fname = format!("some/file.{}", ending);
+            assert!(FileType::try_from_filename(&fname).is_err());
+            assert!(FileType::artifact_of(&fname).is_some());
+        }
+        assert_eq!(FileType::artifact_of(".RData"), Some(FileType::R));
+        assert_eq!(FileType::artifact_of("main.py"), None);
+    }
+
+    #[test]
+    fn try_from_name_ok() {
+        assert_eq!(FileType::try_from_name("rust").unwrap(), FileType::Rust);
+        assert_eq!(FileType::try_from_name("C++").unwrap(), FileType::CPP);
+        assert_eq!(FileType::try_from_name("py").unwrap(), FileType::Python);
+        assert!(FileType::try_from_name("kebab").is_err());
+    }
+
+    #[test]
+    fn all_has_file_endings() {
+        for filetype in ALL {
+            for file_ending in filetype.file_endings() {
+                assert_eq!(&FileType::try_from_filename(file_ending).unwrap(), filetype);
+            }
+        }
+    }
+
+    #[test]
+    fn block_comment_style_ok() {
+        assert_eq!(
+            FileType::Rust.comment_style(Style::Block),
+            CommentStyle::new("/*", "* ", "*/")
+        );
+        assert!(FileType::Perl.comment_style(Style::Block).is_padded());
+        assert_eq!(
+            FileType::Vim.comment_style(Style::Block),
+            CommentStyle::line("\" ")
+        );
+    }
+
+    #[test]
+    fn custom_language() {
+        let jsonnet = Language::new("Jsonnet", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["jsonnet-test", "libsonnet-test"])
+            .with_file_names(&["Jsonnetfile-test"])
+            .with_interpreters(&["jsonnet-test"])
+            .with_line_comment("// ")
+            .with_style(Style::Line)
+            .with_preamble(Regex::new(r"^#\s*pragma\b").unwrap())
+            .register()
+            .unwrap();
+        assert!(jsonnet.is_custom());
+        assert_eq!(jsonnet.to_string(), "Jsonnet");
+        assert!(FileType::registry().contains(&jsonnet));
+        assert_eq!(FileType::try_from_name("jsonnet").unwrap(), jsonnet);
+        assert_eq!(
+            FileType::try_from_filename("a/b.libsonnet-test").unwrap(),
+            jsonnet
+        );
+        assert_eq!(
+            FileType::try_from_filename("a/Jsonnetfile-test").unwrap(),
+            jsonnet
+        );
+        assert_eq!(FileType::from_interpreter("jsonnet-test"), Some(jsonnet));
+        assert_eq!(jsonnet.get_comment_style(), CommentStyle::line("// "));
+        assert_eq!(
+            jsonnet.preamble_len(
+                "#!/bin/jsonnet-test
+# pragma x
+{}
+"
+            ),
+            31
+        );
+    }
+
+    #[test]
+    fn comment_style_line() {
+        assert_eq!(
+            FileType::Rust.comment_style(Style::Line),
+            CommentStyle::line("// ")
+        );
+        assert_eq!(
+            FileType::HTML.comment_style(Style::Line),
+            FileType::HTML.comment_style(Style::Block)
+        );
+        assert_eq!(
+            FileType::Elixir.get_comment_style(),
+            CommentStyle::line("# ")
+        );
+        assert_eq!(FileType::Rust.comment_styles().len(), 2);
+        assert_eq!(FileType::HTML.comment_styles().len(), 1);
     }
 }
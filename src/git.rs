@@ -0,0 +1,381 @@
+/*
+* MIT License
+*
+* Copyright (c) 2023 Wilhelm Ågren
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*
+* File created: 2026-10-18
+* Last updated: 2026-10-18
+*/
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{Error, Result};
+
+/// The line identifying a git hook written by docstring-rs, so that it can be
+/// replaced again without overwriting hooks written by anyone else.
+pub const HOOK_MARKER: &str = "# Installed by docstring-rs install-hook.";
+
+/// Run git in `dir` with `args`, returning what it printed.
+fn git(dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    // the subcommand is the first argument that is not an option, or the value
+    // of a `-c` option
+    let command = args
+        .iter()
+        .zip(std::iter::once(&"").chain(args))
+        .find(|(a, previous)| !a.starts_with('-') && **previous != "-c")
+        .map_or("", |(a, _)| a);
+    let failed = |reason: String| Error::Git {
+        path: dir.to_path_buf(),
+        command: command.to_owned(),
+        reason,
+    };
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .map_err(|e| failed(format!("could not run git: {e}")))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(failed(stderr.trim().to_owned()));
+    }
+
+    Ok(output.stdout)
+}
+
+/// Run git in `dir` with `args` that list files, NUL-separated and relative to the
+/// top of the repository, returning them relative to `dir`.
+fn git_files(dir: &Path, args: &[&str]) -> Result<Vec<PathBuf>> {
+    let cdup = git(dir, &["rev-parse", "--show-cdup"])?;
+    let top = PathBuf::from(String::from_utf8_lossy(&cdup).trim());
+    let listed = git(dir, args)?;
+    Ok(listed
+        .split(|b| *b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| top.join(String::from_utf8_lossy(name).as_ref()))
+        .collect())
+}
+
+/// The top directory of the git repository containing `dir`.
+pub fn toplevel(dir: &Path) -> Result<PathBuf> {
+    let top = git(dir, &["rev-parse", "--show-toplevel"])?;
+    Ok(PathBuf::from(String::from_utf8_lossy(&top).trim()))
+}
+
+/// Create an empty git repository in `dir`.
+#[cfg(test)]
+pub(crate) fn init(dir: &Path) -> Result<()> {
+    git(dir, &["init", "-q"]).map(|_| ())
+}
+
+/// The files staged in the index of the git repository containing `dir`, relative
+/// to `dir`. Deleted files are left out.
+pub fn staged_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    git_files(
+        dir,
+        &[
+            "diff",
+            "--cached",
+            "--name-only",
+            "-z",
+            "--diff-filter=ACMR",
+        ],
+    )
+}
+
+/// The files of the git repository containing `dir` that changed between the
+/// revision `since` and the working tree, staged or not, relative to `dir`. Deleted
+/// files are left out, and untracked files are only included if `untracked` is set.
+pub fn changed_files(dir: &Path, since: &str, untracked: bool) -> Result<Vec<PathBuf>> {
+    let mut files = git_files(
+        dir,
+        &[
+            "diff",
+            "--name-only",
+            "-z",
+            "--diff-filter=ACMR",
+            since,
+            "--",
+        ],
+    )?;
+    if untracked {
+        files.extend(git_files(
+            dir,
+            &[
+                "ls-files",
+                "-z",
+                "--others",
+                "--exclude-standard",
+                "--full-name",
+                "--",
+                ":/",
+            ],
+        )?);
+    }
+
+    Ok(files)
+}
+
+/// The files among `paths` that have changes which are not staged.
+pub fn unstaged_files(dir: &Path, paths: &[PathBuf]) -> Result<HashSet<PathBuf>> {
+    if paths.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let mut args = vec!["diff", "--name-only", "-z", "--"];
+    args.extend(paths.iter().filter_map(|p| p.to_str()));
+    Ok(git_files(dir, &args)?.into_iter().collect())
+}
+
+/// The contents of `path` as staged in the index, which is what gets committed.
+pub fn staged_contents(dir: &Path, path: &Path) -> Result<Vec<u8>> {
+    git(dir, &["show", &format!(":./{}", path.display())])
+}
+
+/// Stage the current contents of `paths`.
+pub fn stage(dir: &Path, paths: &[PathBuf]) -> Result<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    let mut args = vec!["add", "--"];
+    args.extend(paths.iter().filter_map(|p| p.to_str()));
+    git(dir, &args).map(|_| ())
+}
+
+/// The author, as `Name <email>`, of the commit that added `path` to the git
+/// repository containing `dir`, following renames. `None` if it was never committed.
+pub fn original_author(dir: &Path, path: &Path) -> Result<Option<String>> {
+    let path = path.to_string_lossy();
+    let log = git(
+        dir,
+        &[
+            "log",
+            "--follow",
+            "--diff-filter=A",
+            "--format=%an <%ae>",
+            "--",
+            &path,
+        ],
+    )?;
+    let log = String::from_utf8_lossy(&log);
+    Ok(log.lines().last().map(str::to_owned))
+}
+
+/// The author, as `Name <email>`, of every line of `path` in the git repository
+/// containing `dir`, or `None` for lines that are not committed yet. Empty if
+/// `path` is not tracked.
+pub fn line_authors(dir: &Path, path: &Path) -> Result<Vec<Option<String>>> {
+    let path = path.to_string_lossy();
+    if git(dir, &["ls-files", "-z", "--", &path])?.is_empty() {
+        return Ok(Vec::new());
+    }
+    let blame = git(dir, &["blame", "--line-porcelain", "--", &path])?;
+    let blame = String::from_utf8_lossy(&blame);
+
+    let mut authors = Vec::new();
+    let (mut name, mut email) = ("", "");
+    for line in blame.lines() {
+        if let Some(n) = line.strip_prefix("author ") {
+            name = n;
+        } else if let Some(e) = line.strip_prefix("author-mail ") {
+            email = e.trim_start_matches('<').trim_end_matches('>');
+        } else if line.starts_with('\t') {
+            authors.push(match email {
+                "not.committed.yet" => None,
+                _ => Some(format!("{name} <{email}>")),
+            });
+        }
+    }
+
+    Ok(authors)
+}
+
+/// The author, as `Name <email>`, that git would record for a commit in `dir`, or
+/// `None` if it is not configured.
+pub fn current_author(dir: &Path) -> Option<String> {
+    let ident = git(dir, &["var", "GIT_AUTHOR_IDENT"]).ok()?;
+    let ident = String::from_utf8_lossy(&ident);
+    let end = ident.rfind('>')?;
+    Some(ident[..=end].to_owned())
+}
+
+/// Write `script` as the pre-commit hook of the git repository containing `dir`,
+/// returning its path. A hook that was not written by docstring-rs is only
+/// replaced if `overwrite` is set.
+pub fn install_hook(dir: &Path, script: &str, overwrite: bool) -> Result<PathBuf> {
+    let hooks = git(dir, &["rev-parse", "--git-path", "hooks"])?;
+    let hooks = dir.join(String::from_utf8_lossy(&hooks).trim());
+    let path = hooks.join("pre-commit");
+    let write_error = |source: io::Error| Error::Write {
+        path: path.clone(),
+        source,
+    };
+
+    if let Ok(existing) = fs::read_to_string(&path) {
+        if !overwrite && !existing.contains(HOOK_MARKER) {
+            return Err(Error::HookExists { path });
+        }
+    }
+
+    fs::create_dir_all(&hooks).map_err(write_error)?;
+    fs::write(&path, script).map_err(write_error)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).map_err(write_error)?;
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests_git {
+    use super::*;
+
+    /// Create an empty git repository in a new temporary directory.
+    fn repository() -> PathBuf {
+        let dir = std::env::temp_dir().join(crate::tmp::random_file_name());
+        fs::create_dir_all(&dir).unwrap();
+        init(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn staged_and_unstaged() {
+        let dir = repository();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/a.rs"), "fn a() {}\n").unwrap();
+        fs::write(dir.join("src/b.rs"), "fn b() {}\n").unwrap();
+        fs::write(dir.join("notes.txt"), "hi\n").unwrap();
+        stage(
+            &dir,
+            &[PathBuf::from("src/a.rs"), PathBuf::from("notes.txt")],
+        )
+        .unwrap();
+
+        let staged = staged_files(&dir).unwrap();
+        assert_eq!(
+            staged,
+            vec![PathBuf::from("notes.txt"), PathBuf::from("src/a.rs")]
+        );
+        let in_src = staged_files(&dir.join("src")).unwrap();
+        assert!(in_src.contains(&PathBuf::from("../src/a.rs")));
+
+        fs::write(dir.join("src/a.rs"), "fn a() { 1 }\n").unwrap();
+        let unstaged = unstaged_files(&dir, &staged).unwrap();
+        assert_eq!(unstaged, HashSet::from([PathBuf::from("src/a.rs")]));
+        let a = staged_contents(&dir.join("src"), Path::new("a.rs")).unwrap();
+        assert_eq!(a, b"fn a() {}\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn changed_since_revision() {
+        let dir = repository();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        for name in ["src/a.rs", "src/b.rs", "gone.rs"] {
+            fs::write(dir.join(name), "fn main() {}\n").unwrap();
+        }
+        stage(&dir, &[PathBuf::from(".")]).unwrap();
+        git(
+            &dir,
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-qm",
+                ".",
+            ],
+        )
+        .unwrap();
+
+        fs::write(dir.join("src/a.rs"), "fn a() {}\n").unwrap();
+        fs::remove_file(dir.join("gone.rs")).unwrap();
+        fs::write(dir.join("src/new.rs"), "fn new() {}\n").unwrap();
+        let changed = changed_files(&dir, "HEAD", false).unwrap();
+        assert_eq!(changed, vec![PathBuf::from("src/a.rs")]);
+        let changed = changed_files(&dir.join("src"), "HEAD", true).unwrap();
+        assert_eq!(
+            changed,
+            vec![PathBuf::from("../src/a.rs"), PathBuf::from("../src/new.rs")]
+        );
+        assert!(changed_files(&dir, "no-such-rev", false).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn authors_of_lines() {
+        let dir = repository();
+        let commit = |name: &str| {
+            let email = format!("user.email={}@example.com", name.to_lowercase());
+            let name = format!("user.name={name}");
+            stage(&dir, &[PathBuf::from(".")]).unwrap();
+            git(&dir, &["-c", &name, "-c", &email, "commit", "-qm", "."]).unwrap();
+        };
+        fs::write(dir.join("a.rs"), "fn a() {}\n").unwrap();
+        commit("Ada");
+        fs::write(dir.join("a.rs"), "fn a() {}\nfn b() {}\nfn c() {}\n").unwrap();
+        commit("Bob");
+        fs::write(
+            dir.join("a.rs"),
+            "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\n",
+        )
+        .unwrap();
+
+        let ada = "Ada <ada@example.com>".to_owned();
+        let bob = "Bob <bob@example.com>".to_owned();
+        let author = original_author(&dir, Path::new("a.rs")).unwrap();
+        assert_eq!(author, Some(ada.clone()));
+        let lines = line_authors(&dir, Path::new("a.rs")).unwrap();
+        assert_eq!(lines, vec![Some(ada), Some(bob.clone()), Some(bob), None]);
+
+        fs::write(dir.join("new.rs"), "fn new() {}\n").unwrap();
+        assert_eq!(original_author(&dir, Path::new("new.rs")).unwrap(), None);
+        assert!(line_authors(&dir, Path::new("new.rs")).unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn install_hook_guards_foreign_hooks() {
+        let dir = repository();
+        let script = format!("#!/bin/sh\n{HOOK_MARKER}\nexit 0\n");
+        let path = install_hook(&dir, &script, false).unwrap();
+        assert_eq!(path, dir.join(".git/hooks/pre-commit"));
+        assert!(install_hook(&dir, &script, false).is_ok());
+
+        fs::write(&path, "#!/bin/sh\nmake lint\n").unwrap();
+        let e = install_hook(&dir, &script, false).unwrap_err();
+        assert!(matches!(e, Error::HookExists { .. }));
+        install_hook(&dir, &script, true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), script);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn outside_repository() {
+        let e = staged_files(Path::new("/")).unwrap_err();
+        assert!(matches!(e, Error::Git { command, .. } if command == "rev-parse"));
+    }
+}
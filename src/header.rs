@@ -0,0 +1,307 @@
+/*
+* MIT License
+*
+* Copyright (c) 2023 Wilhelm Ågren
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*
+* File created: 2026-10-18
+* Last updated: 2026-10-18
+*/
+
+use std::ops::Range;
+
+use crate::license::looks_like_license;
+use crate::{CommentStyle, FIELDS};
+
+/// Find the byte range of the first multiline comment in `contents` that is
+/// opened and closed with the markers of `cs`, including the trailing newline.
+/// The closing marker may be indented, as in ` */`. For a line comment style,
+/// this is the first run of consecutive comment lines instead.
+pub fn find_header(contents: &str, cs: &CommentStyle) -> Option<Range<usize>> {
+    if cs.is_line() {
+        return find_line_header(contents, cs.normal());
+    }
+
+    let start = cs.start();
+    let end = cs.end();
+
+    let mut header_start: Option<usize> = None;
+    let mut num_chars = 0;
+    for line in contents.split('\n') {
+        match header_start {
+            None => {
+                if line.starts_with(start) {
+                    header_start = Some(num_chars);
+                }
+            }
+            Some(s) => {
+                if line.trim_start().starts_with(end) {
+                    let header_end = (num_chars + line.len() + 1).min(contents.len());
+                    return Some(s..header_end);
+                }
+            }
+        }
+        num_chars += line.len() + 1;
+    }
+
+    None
+}
+
+/// Find the byte range of the first run of lines in `contents` commented with
+/// `prefix`, including the trailing newline. Lines with only the comment marker,
+/// such as the `//` of an empty line, are part of the run.
+fn find_line_header(contents: &str, prefix: &str) -> Option<Range<usize>> {
+    let is_comment = |line: &str| line.starts_with(prefix) || line.trim_end() == prefix.trim_end();
+
+    let mut header: Option<Range<usize>> = None;
+    let mut num_chars = 0;
+    for line in contents.split('\n') {
+        let line_end = (num_chars + line.len() + 1).min(contents.len());
+        match (&mut header, is_comment(line)) {
+            (None, true) => header = Some(num_chars..line_end),
+            (Some(span), true) => span.end = line_end,
+            (Some(_), false) => break,
+            (None, false) => {}
+        }
+        num_chars += line.len() + 1;
+    }
+
+    header
+}
+
+/// Whether or not only whitespace comes between the preamble of `contents`,
+/// ending at `from`, and `at`.
+pub fn is_leading(contents: &str, from: usize, at: usize) -> bool {
+    from <= at && contents[from..at].trim().is_empty()
+}
+
+/// Find the byte range of the license header of `contents`, i.e. a leading comment
+/// that was generated by docstring-rs or that declares a license. Other comments,
+/// such as a description of the file, are not license headers. The header must
+/// follow the preamble of `contents`, e.g. a shebang, which ends at `from`.
+pub fn find_license_header(contents: &str, cs: &CommentStyle, from: usize) -> Option<Range<usize>> {
+    let span = find_header(&contents[from..], cs)?;
+    let span = from + span.start..from + span.end;
+    if !is_leading(contents, from, span.start) {
+        return None;
+    }
+
+    let header = &contents[span.clone()];
+    match is_generated(header) || looks_like_license(&uncomment(header, cs)) {
+        true => Some(span),
+        false => None,
+    }
+}
+
+/// Remove the first docstring found in `contents`, keeping everything else.
+pub fn remove_header(contents: &str, cs: &CommentStyle) -> String {
+    match find_header(contents, cs) {
+        Some(span) => contents[..span.start].to_owned() + &contents[span.end..],
+        None => contents.to_owned(),
+    }
+}
+
+/// The text of the docstring `header` without its comment markers.
+pub fn uncomment(header: &str, cs: &CommentStyle) -> String {
+    let mut lines: Vec<&str> = header.trim_end_matches('\n').split('\n').collect();
+    // a line comment has no markers to open and close it
+    if !cs.is_line() {
+        if lines.len() > 1
+            && lines
+                .last()
+                .is_some_and(|l| l.trim_start().starts_with(cs.end()))
+        {
+            lines.pop();
+        }
+        if lines.first().is_some_and(|l| l.starts_with(cs.start())) {
+            lines.remove(0);
+        }
+    }
+
+    let normal = cs.normal();
+    lines
+        .iter()
+        .map(|l| {
+            let l = match normal.starts_with(' ') {
+                true => l,
+                false => l.trim_start(),
+            };
+            match l.strip_prefix(normal) {
+                Some(s) => s,
+                None => l.strip_prefix(normal.trim_end()).unwrap_or(l),
+            }
+        })
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+/// The fields naming the authors in the uncommented docstring `text`, as pairs of
+/// name and value.
+pub fn header_fields(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|l| l.trim().split_once(": "))
+        .filter(|(name, _)| FIELDS.contains(name))
+        .map(|(name, value)| (name.to_owned(), value.to_owned()))
+        .collect()
+}
+
+/// Whether or not `header` was generated by docstring-rs, i.e. it records when the
+/// file was created and last updated.
+pub fn is_generated(header: &str) -> bool {
+    header.contains("File created: ") && header.contains("Last updated: ")
+}
+
+/// Remove the blank lines left at `at` after a docstring was cut out of `contents`.
+/// At the top of the file all of them are removed, anywhere else they are collapsed
+/// into a single blank line, unless the docstring was not separated by any.
+pub fn collapse_blank_lines(contents: &str, at: usize) -> String {
+    let (before, after) = contents.split_at(at);
+    let blank = before.ends_with("\n\n") || after.starts_with(['\n', '\r']);
+    let after = after.trim_start_matches(['\n', '\r']);
+    if before.trim().is_empty() {
+        return after.to_owned();
+    }
+
+    let before = before.trim_end_matches(['\n', '\r']);
+    match blank {
+        true => before.to_owned() + "\n\n" + after,
+        false => before.to_owned() + "\n" + after,
+    }
+}
+
+/// Whether or not two docstrings are equal when ignoring their `Last updated` date
+/// and any trailing whitespace.
+pub fn same_header(a: &str, b: &str) -> bool {
+    let lines = |s: &'_ str| -> Vec<String> {
+        s.lines()
+            .filter(|l| !l.contains("Last updated: "))
+            .map(|l| l.trim_end().to_owned())
+            .collect()
+    };
+
+    lines(a) == lines(b)
+}
+
+#[cfg(test)]
+mod tests_header {
+    use super::*;
+
+    #[test]
+    fn remove_header_ok() {
+        let cs = CommentStyle::new("/*", "* ", "*/");
+        let contents = "/*\n* MIT License\n*/\nfn main() {}\n";
+        assert_eq!(remove_header(contents, &cs), "fn main() {}\n");
+    }
+
+    #[test]
+    fn remove_header_unclosed() {
+        let cs = CommentStyle::new("/*", "* ", "*/");
+        let contents = "/*\n* MIT License\nfn main() {}\n";
+        assert_eq!(find_header(contents, &cs), None);
+        assert_eq!(remove_header(contents, &cs), contents);
+    }
+
+    #[test]
+    fn uncomment_ok() {
+        let cs = CommentStyle::new("/*", "* ", "*/");
+        let header = "/*\n* MIT License\n*\n* Copyright (c) 2023\n*/\n";
+        assert_eq!(uncomment(header, &cs), "MIT License\n\nCopyright (c) 2023");
+
+        let indented = "/*\n * MIT License\n *\n * Copyright (c) 2023\n */\n";
+        assert_eq!(find_header(indented, &cs), Some(0..indented.len()));
+        assert_eq!(
+            uncomment(indented, &cs),
+            "MIT License\n\nCopyright (c) 2023"
+        );
+    }
+
+    #[test]
+    fn find_license_header_ok() {
+        let cs = CommentStyle::new("/*", "* ", "*/");
+        let license = "/*\n* Copyright (c) 2023 Someone Else\n*/\nint x;\n";
+        assert_eq!(find_license_header(license, &cs, 0), Some(0..40));
+
+        let shebang = "#!/usr/bin/tcc -run\n".to_owned() + license;
+        assert_eq!(find_license_header(&shebang, &cs, 20), Some(20..60));
+        assert_eq!(find_license_header(&shebang, &cs, 0), None);
+
+        let description = "/*\n* Helpers for parsing.\n*/\nint x;\n";
+        assert_eq!(find_license_header(description, &cs, 0), None);
+
+        let not_leading = "int x;\n/*\n* Copyright (c) 2023 Someone Else\n*/\n";
+        assert_eq!(find_license_header(not_leading, &cs, 0), None);
+    }
+
+    #[test]
+    fn find_header_line_style() {
+        let cs = CommentStyle::line("// ");
+        let contents = "//! Crate docs.\n// MIT License\n//\n// Copyright (c) 2023\n\n// Other\n";
+        assert_eq!(find_header(contents, &cs), Some(16..56));
+        assert_eq!(
+            uncomment(&contents[16..56], &cs),
+            "MIT License\n\nCopyright (c) 2023"
+        );
+        assert_eq!(find_license_header(contents, &cs, 0), None);
+        assert_eq!(find_license_header(contents, &cs, 16), Some(16..56));
+    }
+
+    #[test]
+    fn is_generated_ok() {
+        assert!(is_generated(
+            "/*\n* File created: 2023-10-02\n* Last updated: 2023-10-04\n*/\n"
+        ));
+        assert!(!is_generated("/*\n* Copyright (c) 2023 Someone Else\n*/\n"));
+    }
+
+    #[test]
+    fn header_fields_ok() {
+        let text = "MIT License\n\nAuthor: Jane Doe\nMaintainers: @a, @b\nFile created: 2023-10-02";
+        assert_eq!(
+            header_fields(text),
+            vec![
+                ("Author".to_owned(), "Jane Doe".to_owned()),
+                ("Maintainers".to_owned(), "@a, @b".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn collapse_blank_lines_ok() {
+        assert_eq!(
+            collapse_blank_lines("\n\nfn main() {}\n", 0),
+            "fn main() {}\n"
+        );
+        assert_eq!(
+            collapse_blank_lines("#!/bin/sh\n\n\n\necho\n", 10),
+            "#!/bin/sh\n\necho\n"
+        );
+        assert_eq!(
+            collapse_blank_lines("# frozen_string_literal: true\nputs\n", 30),
+            "# frozen_string_literal: true\nputs\n"
+        );
+    }
+
+    #[test]
+    fn same_header_ignores_last_updated() {
+        let a = "/*\n* \n* Last updated: 2023-10-04\n*/\n";
+        let b = "/*\n*\n* Last updated: 2026-10-18\n*/\n";
+        assert!(same_header(a, b));
+    }
+}
@@ -0,0 +1,569 @@
+/*
+* MIT License
+*
+* Copyright (c) 2023 Wilhelm Ågren
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*
+* File created: 2026-10-18
+* Last updated: 2026-10-18
+*/
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::comment::Style;
+use crate::{CommentStyle, Error, FileType, Result};
+
+/// A Dockerfile parser directive, e.g. `# syntax=docker/dockerfile:1`.
+static DOCKERFILE_DIRECTIVE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^#[ \t]*[A-Za-z]+[ \t]*=").expect("could not compile regex"));
+
+/// The encoding declaration of a Python module, e.g. `# -*- coding: utf-8 -*-`,
+/// which must be on its first or second line.
+static PYTHON_ENCODING: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[ \t\f]*#[^\n]*?coding[:=][ \t]*[-_.a-zA-Z0-9]+")
+        .expect("could not compile regex")
+});
+
+/// A magic comment of Ruby, e.g. `# frozen_string_literal: true`, which is
+/// only honoured in the comments at the top of the file.
+static RUBY_MAGIC_COMMENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^#[ \t]*(-\*-[^\n]*-\*-|(frozen_string_literal|encoding|coding|warn_indent|warn_past_scope|shareable_constant_value)[ \t]*:)",
+    )
+    .expect("could not compile regex")
+});
+
+/// The `@charset` rule of a stylesheet, which must be its very first statement.
+static CSS_CHARSET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^@charset\b").expect("could not compile regex"));
+
+/// Lookup table from every built-in `FileType` to its `Language`.
+static BUILTIN: Lazy<HashMap<FileType, Language>> = Lazy::new(|| {
+    FileType::all()
+        .iter()
+        .filter_map(|ft| Some((*ft, builtin(*ft)?)))
+        .collect()
+});
+
+/// The languages registered at runtime, indexed by their `LanguageId`. Entries are
+/// leaked so that they can be handed out for the rest of the program.
+#[cfg(not(test))]
+static REGISTRY: Lazy<RwLock<Vec<&'static Language>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+// Every test runs on a thread of its own, and so gets a registry of its own, so
+// that the languages one test registers never change what the others see.
+#[cfg(test)]
+thread_local! {
+    static REGISTRY: RwLock<Vec<&'static Language>> = const { RwLock::new(Vec::new()) };
+}
+
+/// Run `f` with the registry of languages.
+#[cfg(not(test))]
+fn with_registry<T>(f: impl FnOnce(&RwLock<Vec<&'static Language>>) -> T) -> T {
+    f(&REGISTRY)
+}
+
+/// Run `f` with the registry of languages of the current test.
+#[cfg(test)]
+fn with_registry<T>(f: impl FnOnce(&RwLock<Vec<&'static Language>>) -> T) -> T {
+    REGISTRY.with(f)
+}
+
+/// The index of a language registered at runtime.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct LanguageId(usize);
+
+/// Everything docstring-rs knows about a language: how its files are recognised,
+/// how it writes comments and which lines have to stay above the docstring.
+#[derive(Debug, Clone)]
+pub struct Language {
+    name: &'static str,
+    aliases: Vec<&'static str>,
+    endings: Vec<&'static str>,
+    file_names: Vec<&'static str>,
+    interpreters: Vec<&'static str>,
+    artifacts: Vec<&'static str>,
+    block_comment: CommentStyle<'static>,
+    line_comment: Option<&'static str>,
+    style: Style,
+    preamble: Vec<Regex>,
+}
+
+impl Language {
+    /// A language called `name`, without any files yet, whose docstrings are
+    /// written in block style as the multiline comment `block_comment`. Languages
+    /// without one, such as Vim, use their line comment, `CommentStyle::line`.
+    pub fn new(name: &'static str, block_comment: CommentStyle<'static>) -> Self {
+        Language {
+            name,
+            aliases: Vec::new(),
+            endings: Vec::new(),
+            file_names: Vec::new(),
+            interpreters: Vec::new(),
+            artifacts: Vec::new(),
+            block_comment,
+            line_comment: None,
+            style: Style::Block,
+            preamble: Vec::new(),
+        }
+    }
+
+    /// Also call the language by any of the `aliases`, e.g. the `shell-script`
+    /// mode of Emacs.
+    pub fn with_aliases(mut self, aliases: &[&'static str]) -> Self {
+        self.aliases.extend(aliases);
+        self
+    }
+
+    /// Recognise files with any of the file `endings`, e.g. `rs`.
+    pub fn with_endings(mut self, endings: &[&'static str]) -> Self {
+        self.endings.extend(endings);
+        self
+    }
+
+    /// Recognise files by their full name, e.g. `Makefile`.
+    pub fn with_file_names(mut self, names: &[&'static str]) -> Self {
+        self.file_names.extend(names);
+        self
+    }
+
+    /// Recognise scripts whose shebang runs any of the `interpreters`, e.g. `perl`.
+    pub fn with_interpreters(mut self, interpreters: &[&'static str]) -> Self {
+        self.interpreters.extend(interpreters);
+        self
+    }
+
+    /// Skip binary artifacts built from the language with any of the file
+    /// `endings`, e.g. `pyc`.
+    pub fn with_artifacts(mut self, endings: &[&'static str]) -> Self {
+        self.artifacts.extend(endings);
+        self
+    }
+
+    /// Write docstrings in line style with every line prefixed by `prefix`, e.g. `// `.
+    pub fn with_line_comment(mut self, prefix: &'static str) -> Self {
+        self.line_comment = Some(prefix);
+        self
+    }
+
+    /// Write docstrings in `style` unless chosen otherwise.
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Keep lines at the top of the file matching `pattern` above the docstring.
+    pub fn with_preamble(mut self, pattern: Regex) -> Self {
+        self.preamble.push(pattern);
+        self
+    }
+
+    /// The name of the language, e.g. `C++`.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn aliases(&self) -> &[&'static str] {
+        &self.aliases
+    }
+
+    pub fn endings(&self) -> &[&'static str] {
+        &self.endings
+    }
+
+    pub fn file_names(&self) -> &[&'static str] {
+        &self.file_names
+    }
+
+    pub fn interpreters(&self) -> &[&'static str] {
+        &self.interpreters
+    }
+
+    pub fn artifacts(&self) -> &[&'static str] {
+        &self.artifacts
+    }
+
+    /// The multiline comment of the language. Languages without one, such as Vim,
+    /// use their line comment instead.
+    pub fn block_comment(&self) -> CommentStyle<'static> {
+        self.block_comment.clone()
+    }
+
+    pub fn line_comment(&self) -> Option<&'static str> {
+        self.line_comment
+    }
+
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    /// Patterns of the lines at the top of a file, after its shebang, that have to
+    /// stay above the docstring.
+    pub fn preamble(&self) -> &[Regex] {
+        &self.preamble
+    }
+
+    /// Add the language to the registry of file types, so that its files are found
+    /// and given docstrings. Registering a language with the name of an earlier one
+    /// replaces it. Its file endings and names take precedence over the built-in
+    /// languages, but it cannot reuse the name of a built-in language.
+    pub fn register(self) -> Result<FileType> {
+        let invalid = |reason: String| Error::Config {
+            path: Default::default(),
+            reason,
+        };
+        if self.name.trim().is_empty() {
+            return Err(invalid("a language has no name".to_owned()));
+        }
+        if let Some(ft) = FileType::all()
+            .iter()
+            .find(|ft| ft.to_string().eq_ignore_ascii_case(self.name))
+        {
+            return Err(invalid(format!(
+                "language `{}` is built in as `{ft}`",
+                self.name
+            )));
+        }
+        if self.style == Style::Line && self.line_comment.is_none() {
+            return Err(invalid(format!(
+                "language `{}` has line style but no line comment",
+                self.name
+            )));
+        }
+
+        let language: &'static Language = Box::leak(Box::new(self));
+        let index = with_registry(|registry| {
+            let mut registry = registry.write().expect("language registry is poisoned");
+            match registry
+                .iter()
+                .position(|l| l.name.eq_ignore_ascii_case(language.name))
+            {
+                Some(i) => {
+                    registry[i] = language;
+                    i
+                }
+                None => {
+                    registry.push(language);
+                    registry.len() - 1
+                }
+            }
+        });
+
+        Ok(FileType::Custom(LanguageId(index)))
+    }
+}
+
+/// The `Language` of `file_type`.
+pub(crate) fn language(file_type: FileType) -> &'static Language {
+    match file_type {
+        FileType::Custom(LanguageId(i)) => {
+            with_registry(|registry| registry.read().expect("language registry is poisoned")[i])
+        }
+        ft => &BUILTIN[&ft],
+    }
+}
+
+/// Every language registered at runtime, in the order they were registered.
+pub(crate) fn registered() -> Vec<FileType> {
+    let len = with_registry(|registry| {
+        registry
+            .read()
+            .expect("language registry is poisoned")
+            .len()
+    });
+    (0..len).map(|i| FileType::Custom(LanguageId(i))).collect()
+}
+
+/// The definition of the built-in `file_type`, `None` for the languages registered
+/// at runtime.
+fn builtin(file_type: FileType) -> Option<Language> {
+    use FileType::*;
+    let language = match file_type {
+        C => Language::new("C", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["c"])
+            .with_line_comment("// "),
+        CMake => Language::new("CMake", CommentStyle::new("##", "# ", "##"))
+            .with_endings(&["cmake"])
+            .with_file_names(&["CMakeLists.txt"])
+            .with_line_comment("# "),
+        Config => Language::new("Config", CommentStyle::new("##", "# ", "##"))
+            .with_aliases(&["dosini"])
+            .with_endings(&["cfg", "ini", "conf"])
+            .with_line_comment("# "),
+        CPP => Language::new("C++", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["cc", "cpp", "cxx"])
+            .with_line_comment("// "),
+        CSharp => Language::new("C#", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["cs"])
+            .with_line_comment("// "),
+        CSS => Language::new("CSS", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["css"])
+            .with_preamble(CSS_CHARSET.clone()),
+        Cython => Language::new("Cython", CommentStyle::new("\"\"\"", "", "\"\"\""))
+            .with_endings(&["pyx"])
+            .with_line_comment("# ")
+            .with_style(Style::Line)
+            .with_preamble(PYTHON_ENCODING.clone()),
+        Dockerfile => Language::new("Dockerfile", CommentStyle::new("##", "# ", "##"))
+            .with_endings(&["dockerfile"])
+            .with_file_names(&["Dockerfile", "Containerfile"])
+            .with_line_comment("# ")
+            .with_preamble(DOCKERFILE_DIRECTIVE.clone()),
+        Elixir => Language::new("Elixir", CommentStyle::new("# ", "# ", "# "))
+            .with_endings(&["ex", "exs"])
+            .with_interpreters(&["elixir"])
+            .with_line_comment("# ")
+            .with_style(Style::Line),
+        Erlang => Language::new("Erlang", CommentStyle::new("%", "% ", "%"))
+            .with_endings(&["erl", "hrl"])
+            .with_interpreters(&["escript"])
+            .with_line_comment("%% ")
+            .with_style(Style::Line),
+        FSharp => Language::new("FSharp", CommentStyle::new("(*", "* ", "*)"))
+            .with_endings(&["fs", "fsi", "fsx", "fsscript"])
+            .with_interpreters(&["dotnet", "fsharpi", "fsi"])
+            .with_line_comment("// "),
+        Go => Language::new("Go", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["go"])
+            .with_line_comment("// "),
+        GraphQL => Language::new("GraphQL", CommentStyle::new("##", "# ", "##"))
+            .with_endings(&["graphql", "gql"])
+            .with_line_comment("# "),
+        Haskell => Language::new("Haskell", CommentStyle::new("{-", "- ", "-}"))
+            .with_endings(&["hs", "lhs"])
+            .with_interpreters(&["runghc", "runhaskell"])
+            .with_line_comment("-- "),
+        HCL => Language::new("HCL", CommentStyle::new("##", "# ", "##"))
+            .with_endings(&["tf", "tfvars", "hcl"])
+            .with_line_comment("# "),
+        HolyC => Language::new("HolyC", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["HC"])
+            .with_line_comment("// "),
+        HTML => Language::new("HTML", CommentStyle::new("<!--", "", "-->"))
+            .with_endings(&["html", "htm", "xhtml"]),
+        Java => Language::new("Java", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["java"])
+            .with_line_comment("// "),
+        JavaScript => Language::new("JavaScript", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["js"])
+            .with_interpreters(&["node", "nodejs", "bun"])
+            .with_line_comment("// "),
+        Julia => Language::new("Julia", CommentStyle::new("#=", "= ", "=#"))
+            .with_endings(&["jl"])
+            .with_interpreters(&["julia"])
+            .with_line_comment("# "),
+        Kotlin => Language::new("Kotlin", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["kt", "kts"])
+            .with_interpreters(&["kscript"])
+            .with_line_comment("// "),
+        LESS => Language::new("LESS", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["less"])
+            .with_line_comment("// ")
+            .with_preamble(CSS_CHARSET.clone()),
+        Lisp => Language::new("Lisp", CommentStyle::new(";;;;", ";;;; ", ";;;;"))
+            .with_aliases(&["emacs-lisp", "common-lisp"])
+            .with_endings(&["lisp", "lsp", "l", "cl"])
+            .with_interpreters(&["sbcl", "clisp", "ecl", "ccl"])
+            .with_artifacts(&["fasl"])
+            .with_line_comment(";;;; ")
+            .with_style(Style::Line),
+        Lua => Language::new("Lua", CommentStyle::new("--[[", "-- ", "--]]"))
+            .with_endings(&["lua"])
+            .with_interpreters(&["lua", "luajit"])
+            .with_line_comment("-- "),
+        Makefile => Language::new("Makefile", CommentStyle::new("##", "# ", "##"))
+            .with_aliases(&["make"])
+            .with_endings(&["mk"])
+            .with_interpreters(&["make"])
+            .with_file_names(&["Makefile", "makefile", "GNUmakefile"])
+            .with_line_comment("# "),
+        Markdown => Language::new("Markdown", CommentStyle::new("<!--", "", "-->"))
+            .with_endings(&["md", "markdown"]),
+        Nix => Language::new("Nix", CommentStyle::new("##", "# ", "##"))
+            .with_endings(&["nix"])
+            .with_line_comment("# "),
+        Perl => Language::new("Perl", CommentStyle::new("=pod", "", "=cut").padded())
+            .with_aliases(&["cperl"])
+            .with_endings(&["pl", "plx", "pm", "xs", "t", "pod", "cgi"])
+            .with_interpreters(&["perl"])
+            .with_line_comment("# "),
+        PHP => Language::new("PHP", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["php", "phtml", "pht", "phps"])
+            .with_interpreters(&["php"])
+            .with_artifacts(&["phar"])
+            .with_line_comment("// "),
+        PowerShell => Language::new("PowerShell", CommentStyle::new("<#", "# ", "#>"))
+            .with_endings(&["ps1", "psc1", "pssc"])
+            .with_interpreters(&["pwsh", "powershell"])
+            .with_line_comment("# "),
+        Prolog => Language::new("Prolog", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["pro", "P"])
+            .with_interpreters(&["swipl", "gprolog", "yap"])
+            .with_line_comment("% "),
+        Protobuf => Language::new("Protobuf", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["proto"])
+            .with_line_comment("// "),
+        Python => Language::new("Python", CommentStyle::new("\"\"\"", "", "\"\"\""))
+            .with_endings(&["py", "pyi", "pyw"])
+            .with_interpreters(&["python", "pypy"])
+            .with_artifacts(&["pyc", "pyo", "pyd", "pyz"])
+            .with_line_comment("# ")
+            .with_style(Style::Line)
+            .with_preamble(PYTHON_ENCODING.clone()),
+        QSharp => Language::new("QSharp", CommentStyle::new("///", "///", "///"))
+            .with_endings(&["qs"])
+            .with_line_comment("// ")
+            .with_style(Style::Line),
+        R => Language::new("R", CommentStyle::new("#", "# ", "#"))
+            .with_endings(&["r"])
+            .with_interpreters(&["Rscript", "R"])
+            .with_artifacts(&["rdata", "rda", "rds"])
+            .with_line_comment("# ")
+            .with_style(Style::Line),
+        Ruby => Language::new("Ruby", CommentStyle::new("=begin", "", "=end"))
+            .with_endings(&["rb"])
+            .with_interpreters(&["ruby"])
+            .with_line_comment("# ")
+            .with_preamble(RUBY_MAGIC_COMMENT.clone()),
+        Rust => Language::new("Rust", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["rs"])
+            .with_line_comment("// "),
+        Scala => Language::new("Scala", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["scala", "sc"])
+            .with_interpreters(&["scala"])
+            .with_line_comment("// "),
+        SCSS => Language::new("SCSS", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["scss"])
+            .with_line_comment("// ")
+            .with_preamble(CSS_CHARSET.clone()),
+        Shell => Language::new("Shell", CommentStyle::new("##", "# ", "##"))
+            .with_aliases(&["shell-script"])
+            .with_endings(&["sh", "bash", "zsh", "ksh"])
+            .with_interpreters(&["sh", "bash", "zsh", "ksh", "dash", "ash"])
+            .with_line_comment("# "),
+        SQL => Language::new("SQL", CommentStyle::new("---", "-- ", "---"))
+            .with_endings(&["sql"])
+            .with_line_comment("-- "),
+        Starlark => Language::new("Starlark", CommentStyle::new("##", "# ", "##"))
+            .with_endings(&["bzl", "star"])
+            .with_file_names(&[
+                "BUILD",
+                "BUILD.bazel",
+                "WORKSPACE",
+                "WORKSPACE.bazel",
+                "MODULE.bazel",
+            ])
+            .with_line_comment("# "),
+        Svelte => {
+            Language::new("Svelte", CommentStyle::new("<!--", "", "-->")).with_endings(&["svelte"])
+        }
+        Swift => Language::new("Swift", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["swift", "SWIFT"])
+            .with_interpreters(&["swift"])
+            .with_line_comment("// "),
+        Thrift => Language::new("Thrift", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["thrift"])
+            .with_line_comment("// "),
+        TOML => Language::new("TOML", CommentStyle::new("##", "# ", "##"))
+            .with_endings(&["toml"])
+            .with_line_comment("# "),
+        TypeScript => Language::new("TypeScript", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["ts", "tsx", "mts", "cts"])
+            .with_interpreters(&["deno", "ts-node", "tsx"])
+            .with_line_comment("// "),
+        Vim => Language::new("Vim", CommentStyle::line("\" "))
+            .with_endings(&["vim"])
+            .with_line_comment("\" ")
+            .with_style(Style::Line),
+        Vue => Language::new("Vue", CommentStyle::new("<!--", "", "-->")).with_endings(&["vue"]),
+        XML => {
+            Language::new("XML", CommentStyle::new("<!--", "", "-->")).with_endings(&["xml", "svg"])
+        }
+        YAML => Language::new("YAML", CommentStyle::new("##", "# ", "##"))
+            .with_endings(&["yml", "yaml"])
+            .with_line_comment("# "),
+        Zig => Language::new("Zig", CommentStyle::new("/*", "* ", "*/"))
+            .with_endings(&["zig", "zir"])
+            .with_line_comment("// "),
+        Custom(_) => return None,
+    };
+    Some(language)
+}
+
+#[cfg(test)]
+mod tests_language {
+    use super::*;
+
+    #[test]
+    fn builtin_is_complete() {
+        for ft in FileType::all() {
+            let language = language(*ft);
+            assert_eq!(language.name(), ft.to_string());
+            assert!(!language.endings().is_empty() || !language.file_names().is_empty());
+        }
+    }
+
+    #[test]
+    fn register_replaces_by_name() {
+        let first = Language::new("Falafel", CommentStyle::line("~ "))
+            .with_endings(&["falafel"])
+            .with_line_comment("~ ")
+            .register()
+            .unwrap();
+        let second = Language::new("falafel", CommentStyle::new("<~", "~ ", "~>"))
+            .with_endings(&["flf"])
+            .register()
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(language(second).endings(), ["flf"]);
+        assert!(registered().contains(&second));
+    }
+
+    #[test]
+    fn register_in_other_test() {
+        std::thread::spawn(|| {
+            Language::new("Leaky", CommentStyle::line("# "))
+                .with_endings(&["leaky"])
+                .register()
+                .unwrap()
+        })
+        .join()
+        .unwrap();
+        assert!(registered().is_empty());
+    }
+
+    #[test]
+    fn register_errors() {
+        let rust = Language::new("rust", CommentStyle::line("// ")).register();
+        assert!(rust.unwrap_err().to_string().contains("built in as `Rust`"));
+        assert!(Language::new("Blocky", CommentStyle::new("{", "", "}"))
+            .with_style(Style::Line)
+            .register()
+            .is_err());
+        assert!(Language::new(" ", CommentStyle::line("# "))
+            .with_line_comment("# ")
+            .register()
+            .is_err());
+    }
+}
@@ -0,0 +1,1454 @@
+/*
+* MIT License
+*
+* Copyright (c) 2023 Wilhelm Ågren
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*
+* File created: 2026-10-18
+* Last updated: 2026-10-18
+*/
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use glob::Pattern;
+use log::info;
+
+mod audit;
+mod authors;
+mod comment;
+mod config;
+mod detect;
+mod docstring;
+mod error;
+mod filetype;
+mod git;
+mod header;
+mod language;
+mod license;
+mod tmp;
+mod walk;
+mod watch;
+
+pub use audit::{classify_str, Audit, Declaration, HeaderKind};
+pub use authors::{
+    obfuscate_email, AuthorSource, Authors, Codeowners, Identity, Mailmap, CODEOWNERS_FILES, FIELDS,
+};
+pub use comment::{CommentStyle, Style};
+pub use config::{Config, Overrides, CONFIG_FILE};
+pub use detect::{detect, disambiguate, LanguageMap};
+pub use docstring::Docstring;
+pub use error::{Error, Result};
+pub use filetype::FileType;
+pub use git::{
+    changed_files, install_hook, stage, staged_contents, staged_files, unstaged_files, HOOK_MARKER,
+};
+pub use header::{find_header, is_generated, remove_header, uncomment};
+pub use language::{Language, LanguageId};
+pub use license::{identify, similarity, License, MATCH_THRESHOLD};
+pub use walk::{collect_files, find_files, parse_file_list, Filter};
+pub use watch::{Debouncer, Watch, DEBOUNCE};
+
+use header::{collapse_blank_lines, find_license_header, header_fields, same_header};
+use tmp::tmp_file_from_path;
+use walk::matches_tail;
+
+/// What happened, or would happen, to a file when applying its docstring.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Outcome {
+    /// The file did not exist and was created with the docstring.
+    Created,
+    /// The file had no docstring, so one was added to the top of it.
+    Added,
+    /// The existing docstring was replaced.
+    Updated,
+    /// The existing docstring was removed.
+    Removed,
+    /// The existing docstring is already up to date, or there was no docstring
+    /// to remove.
+    Unchanged,
+    /// The file was left untouched for the given reason.
+    Skipped(Skip),
+}
+
+/// Why a file was left untouched.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Skip {
+    /// The docstring of the file matches neither of the licenses when relicensing.
+    Unmatched,
+    /// The file has a docstring declaring another license, which is not replaced
+    /// unless forced or allowed.
+    ForeignHeader,
+    /// The file is binary, e.g. a compiled `.pyc` or an archived `.phar`.
+    Binary,
+    /// The file is in a directory whose config file disables processing it.
+    Disabled,
+    /// The file is written in a language that is recognised but not supported,
+    /// e.g. Lex for a `.l` file.
+    Unsupported,
+}
+
+impl fmt::Display for Skip {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Skip::Unmatched => write!(f, "docstring matches neither license"),
+            Skip::ForeignHeader => write!(f, "foreign header"),
+            Skip::Binary => write!(f, "binary file"),
+            Skip::Disabled => write!(f, "disabled by config"),
+            Skip::Unsupported => write!(f, "unsupported language"),
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Created => write!(f, "Created"),
+            Outcome::Added => write!(f, "Added"),
+            Outcome::Updated => write!(f, "Updated"),
+            Outcome::Removed => write!(f, "Removed"),
+            Outcome::Unchanged => write!(f, "Unchanged"),
+            Outcome::Skipped(reason) => write!(f, "Skipped ({})", reason),
+        }
+    }
+}
+
+/// How the existing docstring of a file relates to a license.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeaderMatch {
+    /// The file has no docstring.
+    Missing,
+    /// The docstring declares the license of the same copyright holders, possibly
+    /// with different formatting, wrapping or years, so it can safely be
+    /// reformatted.
+    Same { score: f64 },
+    /// The docstring declares another license, or the same license of other
+    /// copyright holders, e.g. vendored code. It is recognised as `license` if it
+    /// is a built-in one, and should be left alone.
+    Foreign { score: f64, license: Option<String> },
+}
+
+/// Options that control how docstrings are applied to files.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Path to the LICENSE file to use as docstring.
+    pub license: PathBuf,
+    /// Use this `FileType` instead of inferring it from the file name.
+    pub file_type: Option<FileType>,
+    /// Globs pinning files to a `FileType`, consulted before the file name.
+    pub languages: LanguageMap,
+    /// Report what would be done to files without writing them.
+    pub dry_run: bool,
+    /// Replace docstrings declaring another license than the configured one.
+    pub force: bool,
+    /// Replace docstrings declaring another license in files matching any of these,
+    /// as if forced.
+    pub allow: Vec<Pattern>,
+    /// Write docstrings in this style instead of the default style of their file type.
+    pub style: Option<Style>,
+    /// Write docstrings of these file types in the given style, taking precedence
+    /// over `style`.
+    pub styles: HashMap<FileType, Style>,
+    /// Name the authors of every file in its docstring.
+    pub authors: Option<Authors>,
+    /// Leave every file alone.
+    pub skip: bool,
+    /// The config files in subdirectories, overriding these options for the files
+    /// below them.
+    pub overrides: Option<Overrides>,
+    /// The options that were given explicitly, e.g. on the command line, which no
+    /// config file overrides.
+    pub explicit: Explicit,
+}
+
+/// Which of the `Options` were given explicitly rather than taken from a default.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Explicit {
+    /// The LICENSE file, set with `with_license`.
+    pub license: bool,
+    /// The style of all file types, set with `with_style`.
+    pub style: bool,
+}
+
+impl Options {
+    /// Create options that read the docstring from the LICENSE file at `license`.
+    pub fn new(license: impl Into<PathBuf>) -> Self {
+        Self {
+            license: license.into(),
+            file_type: None,
+            languages: LanguageMap::default(),
+            dry_run: false,
+            force: false,
+            allow: Vec::new(),
+            style: None,
+            styles: HashMap::new(),
+            authors: None,
+            skip: false,
+            overrides: None,
+            explicit: Explicit::default(),
+        }
+    }
+
+    /// Read the docstring from the LICENSE file at `license`, whatever the config
+    /// files say.
+    pub fn with_license(mut self, license: impl Into<PathBuf>) -> Self {
+        self.license = license.into();
+        self.explicit.license = true;
+        self
+    }
+
+    /// Use `file_type` for every file instead of inferring it from the file name.
+    pub fn with_file_type(mut self, file_type: FileType) -> Self {
+        self.file_type = Some(file_type);
+        self
+    }
+
+    /// Pin the files matched by `languages` to their `FileType`.
+    pub fn with_languages(mut self, languages: LanguageMap) -> Self {
+        self.languages = languages;
+        self
+    }
+
+    /// Only report what would be done to files instead of writing them.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Replace docstrings declaring another license than the configured one.
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Replace docstrings declaring another license in files matching the glob
+    /// `pattern`, e.g. `third_party/**`.
+    pub fn with_allow(mut self, pattern: &str) -> Result<Self> {
+        match Pattern::new(pattern) {
+            Ok(p) => self.allow.push(p),
+            Err(e) => {
+                return Err(Error::Glob {
+                    pattern: pattern.to_owned(),
+                    reason: e.to_string(),
+                })
+            }
+        };
+        Ok(self)
+    }
+
+    /// Write docstrings in `style` instead of the default style of their file type,
+    /// whatever the config files say.
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self.explicit.style = true;
+        self
+    }
+
+    /// Write docstrings of `file_type` in `style`.
+    pub fn with_style_for(mut self, file_type: FileType, style: Style) -> Self {
+        self.styles.insert(file_type, style);
+        self
+    }
+
+    /// Name the authors of every file in its docstring as configured by `authors`.
+    pub fn with_authors(mut self, authors: Option<Authors>) -> Self {
+        self.authors = authors;
+        self
+    }
+
+    /// Override these options for the files below the config files in `overrides`.
+    pub fn with_overrides(mut self, overrides: Option<Overrides>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// The options for the file at `path`, with the config files of the
+    /// directories containing it applied, the nearest one last.
+    pub fn for_path(&self, path: &Path) -> Result<Cow<'_, Options>> {
+        let configs = match &self.overrides {
+            Some(o) => o.configs(path)?,
+            None => return Ok(Cow::Borrowed(self)),
+        };
+        match configs.is_empty() {
+            true => Ok(Cow::Borrowed(self)),
+            false => Ok(Cow::Owned(
+                configs.iter().rev().fold(self.clone(), |o, c| c.apply(o)),
+            )),
+        }
+    }
+
+    /// The style in which docstrings of `file_type` are written.
+    pub fn style_for(&self, file_type: FileType) -> Style {
+        match self.styles.get(&file_type) {
+            Some(style) => *style,
+            None => self.style.unwrap_or(file_type.default_style()),
+        }
+    }
+
+    /// Whether or not a docstring declaring another license may be replaced in the
+    /// file at `path`. Allow rules match the path or any of its trailing components,
+    /// so that `vendor/**` matches `src/vendor/lib.c`.
+    pub fn may_replace_foreign(&self, path: &Path) -> bool {
+        if self.force {
+            return true;
+        }
+
+        self.allow.iter().any(|p| matches_tail(p, path))
+    }
+
+    /// Whether or not `contents` of the file at `path` has a docstring declaring
+    /// another license than `license` which must be left alone.
+    fn protects(&self, path: &Path, contents: &str, license: &License, ft: FileType) -> bool {
+        matches!(
+            match_header_str(contents, license, ft),
+            HeaderMatch::Foreign { .. }
+        ) && !self.may_replace_foreign(path)
+    }
+
+    /// The fields to write in the docstring of `contents` of the file at `path`,
+    /// i.e. its authors if configured.
+    fn fields(&self, path: &Path, contents: &str, ft: FileType) -> Result<Vec<(String, String)>> {
+        let authors = match &self.authors {
+            Some(a) => a,
+            None => return Ok(Vec::new()),
+        };
+        let header = match find_any_license_header(contents, ft) {
+            Some((span, _)) => {
+                let start = contents[..span.start].matches('\n').count();
+                start..start + contents[span].matches('\n').count()
+            }
+            None => 0..0,
+        };
+        authors.fields(path, header)
+    }
+
+    fn resolve_file_type(&self, path: &Path) -> Result<FileType> {
+        match self.file_type {
+            Some(ft) => Ok(ft),
+            None => self.languages.resolve(path),
+        }
+    }
+
+    /// Read the LICENSE file, or the built-in license if `license` is an SPDX id.
+    fn read_license(&self) -> Result<License> {
+        let license = License::resolve(&self.license)?;
+        info!("Read contents of {} successfully", &self.license.display());
+        Ok(license)
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new("LICENSE")
+    }
+}
+
+/// Find the license header of `contents` written in any of the comment styles of
+/// `file_type`, together with the style it is written in.
+fn find_any_license_header(
+    contents: &str,
+    file_type: FileType,
+) -> Option<(Range<usize>, CommentStyle<'static>)> {
+    file_type
+        .header_offsets(contents)
+        .into_iter()
+        .find_map(|from| {
+            file_type
+                .comment_styles()
+                .into_iter()
+                .filter_map(|cs| find_license_header(contents, &cs, from).map(|span| (span, cs)))
+                .min_by_key(|(span, _)| span.start)
+        })
+}
+
+/// Add or update the docstring of `contents`, returning the new contents and
+/// what was done to them. A docstring declaring another license is left alone.
+pub fn apply_header_str(
+    contents: &str,
+    license: &str,
+    file_type: FileType,
+) -> Result<(String, Outcome)> {
+    apply_header_str_with_style(contents, license, file_type, file_type.default_style())
+}
+
+/// Add or update the docstring of `contents` written in `style`. An existing
+/// docstring in the other style is replaced, one declaring another license is
+/// left alone.
+pub fn apply_header_str_with_style(
+    contents: &str,
+    license: &str,
+    file_type: FileType,
+    style: Style,
+) -> Result<(String, Outcome)> {
+    apply_header_str_with_fields(contents, license, file_type, style, &[], false)
+}
+
+/// Add or update the docstring of `contents` written in `style`, with a
+/// `name: value` line for each of `fields` after the license, e.g. its authors.
+/// If `license` is a template, e.g. a built-in license, its placeholder copyright
+/// line is filled in with the copyright lines of the existing docstring. A
+/// docstring declaring another license, or the same license of other copyright
+/// holders, is left alone unless `force` is set.
+pub fn apply_header_str_with_fields(
+    contents: &str,
+    license: &str,
+    file_type: FileType,
+    style: Style,
+    fields: &[(String, String)],
+    force: bool,
+) -> Result<(String, Outcome)> {
+    if !force
+        && matches!(
+            match_header_str(contents, &License::from_text(license), file_type),
+            HeaderMatch::Foreign { .. }
+        )
+    {
+        return Ok((contents.to_owned(), Outcome::Skipped(Skip::ForeignHeader)));
+    }
+    let cs = file_type.comment_style(style);
+    let existing = find_any_license_header(contents, file_type);
+    let holders = match &existing {
+        Some((span, found)) => {
+            license::copyright_holders(&uncomment(&contents[span.clone()], found))
+        }
+        None => Vec::new(),
+    };
+    let license = license::fill_placeholders(license, &holders)?;
+    let existing = existing.map(|(span, _)| span);
+
+    let mut docstring = Docstring::from_license(&license, file_type).with_comment_style(cs.clone());
+    for (name, value) in fields {
+        docstring = docstring.with_field(name, value);
+    }
+    if let Some(span) = &existing {
+        docstring.find_created_date(&contents[span.clone()])?;
+    }
+    docstring.format_contents()?;
+    let formatted = docstring
+        .get_formatted_contents()
+        .expect("docstring contents were just formatted");
+
+    match existing {
+        Some(span) if same_header(&contents[span.clone()], &formatted) => {
+            Ok((contents.to_owned(), Outcome::Unchanged))
+        }
+        Some(span) => {
+            let updated = contents[..span.start].to_owned() + &formatted + &contents[span.end..];
+            Ok((updated, Outcome::Updated))
+        }
+        None => {
+            let (preamble, rest) = contents.split_at(file_type.preamble_len(contents));
+            // the byte order mark stays first, without a line of its own
+            let (bom, preamble) = preamble.split_at(filetype::bom_len(preamble));
+            let mut preamble = match preamble.is_empty() || preamble.ends_with('\n') {
+                true => preamble.to_owned(),
+                false => preamble.to_owned() + "\n",
+            };
+            // padded comments, i.e. POD, must also be preceded by a blank line
+            if cs.is_padded() && !preamble.is_empty() {
+                preamble.push('\n');
+            }
+            // keep line comments apart from any comment the file starts with, and
+            // padded comments apart from the code
+            let separator = match (cs.is_line() || cs.is_padded())
+                && !rest.is_empty()
+                && !rest.starts_with('\n')
+            {
+                true => "\n",
+                false => "",
+            };
+            Ok((
+                bom.to_owned() + &preamble + &formatted + separator + rest,
+                Outcome::Added,
+            ))
+        }
+    }
+}
+
+/// Compare the existing docstring of `contents` against `license`. Docstrings
+/// generated by docstring-rs always count as the same license, since their text
+/// is the previously configured LICENSE. Other docstrings of the same license only
+/// count as the same if they name the same copyright holders, or if either has
+/// none, e.g. because `license` is a template.
+pub fn match_header_str(contents: &str, license: &License, file_type: FileType) -> HeaderMatch {
+    let (header, cs) = match find_any_license_header(contents, file_type) {
+        Some((span, cs)) => (&contents[span], cs),
+        None => return HeaderMatch::Missing,
+    };
+
+    let score = license.score(header, &cs);
+    if is_generated(header) {
+        return HeaderMatch::Same { score };
+    }
+    let text = uncomment(header, &cs);
+    if license.matches(header, &cs) {
+        let ours = license::copyright_holders(license.text());
+        let theirs = license::copyright_holders(&text);
+        if ours.is_empty() || theirs.is_empty() || license::same_holders(&ours, &theirs) {
+            return HeaderMatch::Same { score };
+        }
+    }
+
+    HeaderMatch::Foreign {
+        score,
+        license: license::identify(&text).map(|(id, _)| id.to_owned()),
+    }
+}
+
+/// Check what applying the docstring to `contents` would do, without changing them.
+/// Contents with a docstring declaring another license are `Skipped`.
+pub fn check_header_str(contents: &str, license: &str, file_type: FileType) -> Result<Outcome> {
+    let (_, outcome) = apply_header_str(contents, license, file_type)?;
+    Ok(outcome)
+}
+
+/// Add or update the docstring of the file at `path`. Creates the file, and any
+/// missing parent directories, if it does not already exist. A docstring declaring
+/// another license is left alone unless `options` force or allow replacing it, and
+/// files below a config file disabling them are left alone altogether.
+pub fn apply_header(path: &Path, options: &Options) -> Result<Outcome> {
+    let options = &options.for_path(path)?;
+    if options.skip {
+        return Ok(Outcome::Skipped(Skip::Disabled));
+    }
+    if is_artifact(path) {
+        return Ok(Outcome::Skipped(Skip::Binary));
+    }
+    let file_type = match options.resolve_file_type(path) {
+        Err(Error::UnsupportedLanguage { .. }) => return Ok(Outcome::Skipped(Skip::Unsupported)),
+        r => r?,
+    };
+    let license = options.read_license()?;
+
+    if !path.exists() {
+        if options.dry_run {
+            return Ok(Outcome::Created);
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| write_error(parent, e))?;
+        }
+        let style = options.style_for(file_type);
+        let fields = options.fields(path, "", file_type)?;
+        let (contents, _) =
+            apply_header_str_with_fields("", license.text(), file_type, style, &fields, true)
+                .map_err(|e| e.with_path(path))?;
+        fs::write(path, contents).map_err(|e| write_error(path, e))?;
+        info!("Wrote docstring contents to file: `{}`", &path.display());
+        return Ok(Outcome::Created);
+    }
+
+    let contents = match read_contents(path)? {
+        Some(c) => c,
+        None => return Ok(Outcome::Skipped(Skip::Binary)),
+    };
+    if options.protects(path, &contents, &license, file_type) {
+        info!("Not replacing the foreign header of `{}`", &path.display());
+        return Ok(Outcome::Skipped(Skip::ForeignHeader));
+    }
+    let style = options.style_for(file_type);
+    let fields = options.fields(path, &contents, file_type)?;
+    let (updated, outcome) =
+        apply_header_str_with_fields(&contents, license.text(), file_type, style, &fields, true)
+            .map_err(|e| e.with_path(path))?;
+    if outcome != Outcome::Unchanged && !options.dry_run {
+        write_contents(path, &updated)?;
+        info!("Updated docstring at: `{}`", &path.display());
+    }
+
+    Ok(outcome)
+}
+
+/// Check what applying the docstring to the file at `path` would do, without
+/// changing the file.
+pub fn check_header(path: &Path, options: &Options) -> Result<Outcome> {
+    let options = &options.for_path(path)?;
+    if options.skip {
+        return Ok(Outcome::Skipped(Skip::Disabled));
+    }
+    if is_artifact(path) {
+        return Ok(Outcome::Skipped(Skip::Binary));
+    }
+    if !path.exists() {
+        options.resolve_file_type(path)?;
+        if let Some(line) = options.read_license()?.placeholder() {
+            return Err(Error::Placeholder {
+                path: path.to_path_buf(),
+                line: line.to_owned(),
+            });
+        }
+        return Ok(Outcome::Created);
+    }
+
+    let contents = fs::read(path).map_err(|e| Error::Read {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    check_contents(path, contents, options)
+}
+
+/// Check the docstring of `contents`, which belong to the file at `path` but may
+/// have been read from elsewhere, e.g. the git index. Returns the outcome that
+/// applying the docstring to them would have.
+pub fn check_contents(path: &Path, contents: Vec<u8>, options: &Options) -> Result<Outcome> {
+    let options = &options.for_path(path)?;
+    if options.skip {
+        return Ok(Outcome::Skipped(Skip::Disabled));
+    }
+    if is_artifact(path) {
+        return Ok(Outcome::Skipped(Skip::Binary));
+    }
+    let file_type = match options.resolve_file_type(path) {
+        Err(Error::UnsupportedLanguage { .. }) => return Ok(Outcome::Skipped(Skip::Unsupported)),
+        r => r?,
+    };
+    let license = options.read_license()?;
+
+    let contents = match decode_contents(path, contents)? {
+        Some(c) => c,
+        None => return Ok(Outcome::Skipped(Skip::Binary)),
+    };
+    if options.protects(path, &contents, &license, file_type) {
+        return Ok(Outcome::Skipped(Skip::ForeignHeader));
+    }
+    let style = options.style_for(file_type);
+    let fields = options.fields(path, &contents, file_type)?;
+    let (_, outcome) =
+        apply_header_str_with_fields(&contents, license.text(), file_type, style, &fields, true)
+            .map_err(|e| e.with_path(path))?;
+    Ok(outcome)
+}
+
+/// Add or update the docstring of `contents`, which belong to the file at `path`
+/// but are not read from or written to it, e.g. when used as a filter on stdin.
+/// The `FileType` is inferred from the name of `path`, or from a shebang or
+/// modeline in `contents` if the name does not tell. Binary contents, and contents
+/// with a docstring declaring another license, are returned as they are.
+pub fn format_contents(
+    path: &Path,
+    contents: Vec<u8>,
+    options: &Options,
+) -> Result<(Vec<u8>, Outcome)> {
+    let options = &options.for_path(path)?;
+    if options.skip {
+        return Ok((contents, Outcome::Skipped(Skip::Disabled)));
+    }
+    if is_artifact(path) || detect::is_binary(&contents) {
+        return Ok((contents, Outcome::Skipped(Skip::Binary)));
+    }
+    let text = decode_contents(path, contents)?.unwrap_or_default();
+    let file_type = match options.resolve_file_type(path) {
+        Ok(ft) => ft,
+        Err(Error::UnsupportedLanguage { .. }) => {
+            return Ok((text.into_bytes(), Outcome::Skipped(Skip::Unsupported)))
+        }
+        Err(e) => detect::from_contents(&text).ok_or(e)?,
+    };
+    let license = options.read_license()?;
+
+    if options.protects(path, &text, &license, file_type) {
+        return Ok((text.into_bytes(), Outcome::Skipped(Skip::ForeignHeader)));
+    }
+    let style = options.style_for(file_type);
+    let fields = options.fields(path, &text, file_type)?;
+    let (updated, outcome) =
+        apply_header_str_with_fields(&text, license.text(), file_type, style, &fields, true)
+            .map_err(|e| e.with_path(path))?;
+    Ok((updated.into_bytes(), outcome))
+}
+
+/// Remove the docstring generated by docstring-rs from `contents`, returning the
+/// new contents and whether or not a docstring was removed. Any other leading
+/// comment is left untouched, and blank lines left behind are collapsed.
+pub fn strip_header_str(contents: &str, file_type: FileType) -> (String, Outcome) {
+    match find_any_license_header(contents, file_type) {
+        Some((span, _)) if is_generated(&contents[span.clone()]) => {
+            let removed = contents[..span.start].to_owned() + &contents[span.end..];
+            (collapse_blank_lines(&removed, span.start), Outcome::Removed)
+        }
+        _ => (contents.to_owned(), Outcome::Unchanged),
+    }
+}
+
+/// Remove the docstring of the file at `path`.
+pub fn strip_header(path: &Path, options: &Options) -> Result<Outcome> {
+    let options = &options.for_path(path)?;
+    if options.skip {
+        return Ok(Outcome::Skipped(Skip::Disabled));
+    }
+    if is_artifact(path) {
+        return Ok(Outcome::Skipped(Skip::Binary));
+    }
+    let file_type = match options.resolve_file_type(path) {
+        Err(Error::UnsupportedLanguage { .. }) => return Ok(Outcome::Skipped(Skip::Unsupported)),
+        r => r?,
+    };
+    let contents = match read_contents(path)? {
+        Some(c) => c,
+        None => return Ok(Outcome::Skipped(Skip::Binary)),
+    };
+    let (stripped, outcome) = strip_header_str(&contents, file_type);
+    if outcome == Outcome::Removed && !options.dry_run {
+        write_contents(path, &stripped)?;
+        info!("Removed docstring at: `{}`", &path.display());
+    }
+
+    Ok(outcome)
+}
+
+/// Replace the docstring of `contents` declaring license `from` with one declaring
+/// license `to`, keeping the created date, copyright holders and authors of the
+/// docstring.
+pub fn relicense_str(
+    contents: &str,
+    file_type: FileType,
+    from: &License,
+    to: &License,
+) -> Result<(String, Outcome)> {
+    let (header, cs) = match find_any_license_header(contents, file_type) {
+        Some((span, cs)) => (&contents[span], cs),
+        None => return Ok((contents.to_owned(), Outcome::Skipped(Skip::Unmatched))),
+    };
+
+    if from.matches(header, &cs) {
+        let text = uncomment(header, &cs);
+        let holders = license::copyright_holders(&text);
+        let style = match cs.is_line() {
+            true => Style::Line,
+            false => Style::Block,
+        };
+        let (relicensed, _) = apply_header_str_with_fields(
+            contents,
+            &to.with_holders(&holders),
+            file_type,
+            style,
+            &header_fields(&text),
+            true,
+        )?;
+        return Ok((relicensed, Outcome::Updated));
+    }
+
+    match to.matches(header, &cs) {
+        true => Ok((contents.to_owned(), Outcome::Unchanged)),
+        false => Ok((contents.to_owned(), Outcome::Skipped(Skip::Unmatched))),
+    }
+}
+
+/// Relicense the file at `path` from license `from` to the license of `options`.
+pub fn relicense(path: &Path, options: &Options, from: &License) -> Result<Outcome> {
+    let options = &options.for_path(path)?;
+    if options.skip {
+        return Ok(Outcome::Skipped(Skip::Disabled));
+    }
+    if is_artifact(path) {
+        return Ok(Outcome::Skipped(Skip::Binary));
+    }
+    let file_type = match options.resolve_file_type(path) {
+        Err(Error::UnsupportedLanguage { .. }) => return Ok(Outcome::Skipped(Skip::Unsupported)),
+        r => r?,
+    };
+    let to = License::resolve(&options.license)?;
+    let contents = match read_contents(path)? {
+        Some(c) => c,
+        None => return Ok(Outcome::Skipped(Skip::Binary)),
+    };
+    let (relicensed, outcome) =
+        relicense_str(&contents, file_type, from, &to).map_err(|e| e.with_path(path))?;
+    if outcome == Outcome::Updated && !options.dry_run {
+        write_contents(path, &relicensed)?;
+        info!("Relicensed docstring at: `{}`", &path.display());
+    }
+
+    Ok(outcome)
+}
+
+/// Classify the leading header of the file at `path` and the license it declares,
+/// comparing it against the license of `options`.
+pub fn classify(path: &Path, options: &Options) -> Result<Declaration> {
+    let options = &options.for_path(path)?;
+    if is_artifact(path) {
+        return Ok(Declaration::binary());
+    }
+    let file_type = options.resolve_file_type(path)?;
+    let project = License::resolve(&options.license)?;
+    match read_contents(path)? {
+        Some(contents) => Ok(classify_str(&contents, file_type, &project)),
+        None => Ok(Declaration::binary()),
+    }
+}
+
+/// Whether or not `path` has the file ending of a binary artifact, e.g. `.pyc`.
+fn is_artifact(path: &Path) -> bool {
+    FileType::artifact_of(&path.to_string_lossy()).is_some()
+}
+
+/// Read the contents of the file at `path`, or `None` if it is a binary file.
+fn read_contents(path: &Path) -> Result<Option<String>> {
+    let contents: Vec<u8> = match fs::read(path) {
+        Ok(c) => c,
+        Err(e) => {
+            return Err(Error::Read {
+                path: path.to_path_buf(),
+                source: e,
+            })
+        }
+    };
+    info!("Read contents of `{}` successfully", &path.display());
+    decode_contents(path, contents)
+}
+
+/// Decode the `contents` of the file at `path`, or `None` if it is a binary file.
+fn decode_contents(path: &Path, contents: Vec<u8>) -> Result<Option<String>> {
+    if detect::is_binary(&contents) {
+        return Ok(None);
+    }
+    String::from_utf8(contents)
+        .map(Some)
+        .map_err(|_| Error::Decode {
+            path: path.to_path_buf(),
+        })
+}
+
+fn write_error(path: &Path, source: io::Error) -> Error {
+    Error::Write {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+/// Write `contents` to a temporary file next to `path` and then move it in place,
+/// so that `path` is never left half-written.
+fn write_contents(path: &Path, contents: &str) -> Result<()> {
+    let tmp_path: PathBuf = tmp_file_from_path(path);
+    fs::write(&tmp_path, contents).map_err(|e| write_error(&tmp_path, e))?;
+    info!(
+        "Wrote docstring contents to tmp file: `{}`",
+        &tmp_path.display()
+    );
+
+    let moved = fs::metadata(path)
+        .and_then(|m| fs::set_permissions(&tmp_path, m.permissions()))
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if let Err(e) = moved {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(write_error(path, e));
+    }
+    info!(
+        "Moved contents from `{}` to `{}`",
+        &tmp_path.display(),
+        &path.display()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_lib {
+    use super::*;
+
+    static LICENSE: &str = "MIT License\n\nCopyright (c) 2023 Wilhelm Ågren\n";
+
+    /// Run `interpreter` with `args` on a file called `name` holding `contents`,
+    /// where `{}` in `args` is the path of the file. Returns whether it succeeded
+    /// and what it printed, or `None` if the interpreter is not installed.
+    fn run(
+        interpreter: &str,
+        args: &[&str],
+        name: &str,
+        contents: &str,
+    ) -> Option<(bool, Vec<u8>)> {
+        let dir = std::env::temp_dir().join(tmp::random_file_name());
+        let path = dir.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, contents).unwrap();
+        let output = std::process::Command::new(interpreter)
+            .args(args.iter().map(|a| match *a {
+                "{}" => path.as_os_str(),
+                a => a.as_ref(),
+            }))
+            .stdin(std::process::Stdio::null())
+            .output();
+        fs::remove_dir_all(&dir).unwrap();
+        match output {
+            Ok(o) => Some((o.status.success(), o.stdout)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => panic!("could not run `{interpreter}`: {e}"),
+        }
+    }
+
+    /// Assert that `interpreter` runs `before` and `after` alike, if it is installed.
+    fn assert_runs_alike(interpreter: &str, args: &[&str], name: &str, before: &str, after: &str) {
+        if let Some(expected) = run(interpreter, args, name, before) {
+            assert!(expected.0, "`{interpreter}` rejects {before:?}");
+            assert_eq!(run(interpreter, args, name, after), Some(expected));
+        }
+    }
+
+    #[test]
+    fn apply_header_str_added() {
+        let (contents, outcome) =
+            apply_header_str("fn main() {}\n", LICENSE, FileType::Rust).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+        assert!(contents.starts_with("/*\n* MIT License\n"));
+        assert!(contents.ends_with("*/\nfn main() {}\n"));
+    }
+
+    #[test]
+    fn apply_header_str_unchanged() {
+        let (contents, _) = apply_header_str("fn main() {}\n", LICENSE, FileType::Rust).unwrap();
+        let (again, outcome) = apply_header_str(&contents, LICENSE, FileType::Rust).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+        assert_eq!(contents, again);
+    }
+
+    #[test]
+    fn apply_header_str_after_preamble() {
+        let script = "#!/bin/sh\necho hi\n";
+        let (contents, outcome) = apply_header_str(script, LICENSE, FileType::Shell).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+        assert!(contents.starts_with("#!/bin/sh\n##\n# MIT License\n"));
+        assert!(contents.ends_with("##\necho hi\n"));
+        let (_, outcome) = apply_header_str(&contents, LICENSE, FileType::Shell).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+
+        let dockerfile = "# syntax=docker/dockerfile:1\nFROM alpine\n";
+        let (contents, _) = apply_header_str(dockerfile, LICENSE, FileType::Dockerfile).unwrap();
+        assert!(contents.starts_with("# syntax=docker/dockerfile:1\n##\n"));
+        let (_, outcome) = apply_header_str(&contents, LICENSE, FileType::Dockerfile).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+    }
+
+    #[test]
+    fn apply_header_str_keeps_bom_first() {
+        let (contents, outcome) =
+            apply_header_str("\u{feff}fn main() {}\n", LICENSE, FileType::Rust).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+        assert!(contents.starts_with("\u{feff}/*\n* MIT License\n"));
+        let (_, outcome) = apply_header_str(&contents, LICENSE, FileType::Rust).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+
+        let script = "\u{feff}#!/bin/sh\necho hi\n";
+        let (contents, _) = apply_header_str(script, LICENSE, FileType::Shell).unwrap();
+        assert!(contents.starts_with("\u{feff}#!/bin/sh\n##\n# MIT License\n"));
+    }
+
+    #[test]
+    fn apply_header_str_sql() {
+        let (contents, outcome) = apply_header_str("SELECT 1;\n", LICENSE, FileType::SQL).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+        assert!(contents.starts_with("---\n-- MIT License\n--\n-- Copyright"));
+        let (_, outcome) = apply_header_str(&contents, LICENSE, FileType::SQL).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+        let (stripped, _) = strip_header_str(&contents, FileType::SQL);
+        assert_eq!(stripped, "SELECT 1;\n");
+    }
+
+    #[test]
+    fn apply_header_str_line_style() {
+        let source = "// Helpers for parsing.\nfn main() {}\n";
+        let (contents, outcome) =
+            apply_header_str_with_style(source, LICENSE, FileType::Rust, Style::Line).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+        assert!(contents.starts_with("// MIT License\n//\n// Copyright"));
+        assert!(contents.ends_with("\n\n// Helpers for parsing.\nfn main() {}\n"));
+
+        let (again, outcome) =
+            apply_header_str_with_style(&contents, LICENSE, FileType::Rust, Style::Line).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+        assert_eq!(again, contents);
+
+        let (stripped, outcome) = strip_header_str(&contents, FileType::Rust);
+        assert_eq!(outcome, Outcome::Removed);
+        assert_eq!(stripped, source);
+    }
+
+    #[test]
+    fn apply_header_str_converts_style() {
+        let old = "/*\n* MIT License\n* File created: 2023-10-02\n* Last updated: 2023-10-02\n*/\nfn main() {}\n";
+        let (contents, outcome) =
+            apply_header_str_with_style(old, LICENSE, FileType::Rust, Style::Line).unwrap();
+        assert_eq!(outcome, Outcome::Updated);
+        assert!(contents.starts_with("// MIT License\n"));
+        assert!(contents.contains("// File created: 2023-10-02\n"));
+        assert!(contents.ends_with("\nfn main() {}\n"));
+        assert!(!contents.contains("*/"));
+    }
+
+    #[test]
+    fn apply_header_str_python_module_docstring() {
+        let module = "\"\"\"Helpers for parsing.\"\"\"\nimport os\n";
+        let (contents, outcome) = apply_header_str(module, LICENSE, FileType::Python).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+        assert!(contents.starts_with("# MIT License\n#\n"));
+        assert!(contents.ends_with("\n\n\"\"\"Helpers for parsing.\"\"\"\nimport os\n"));
+        let (_, outcome) = apply_header_str(&contents, LICENSE, FileType::Python).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+
+        let old =
+            "\"\"\"\nMIT License\nFile created: 2023-10-02\nLast updated: 2023-10-02\n\"\"\"\n"
+                .to_owned()
+                + module;
+        let (contents, outcome) = apply_header_str(&old, LICENSE, FileType::Python).unwrap();
+        assert_eq!(outcome, Outcome::Updated);
+        assert!(contents.contains("# File created: 2023-10-02\n"));
+        assert!(contents.ends_with("\"\"\"Helpers for parsing.\"\"\"\nimport os\n"));
+    }
+
+    #[test]
+    fn apply_header_str_rust_inner_docs() {
+        let module = "//! Crate docs.\n#![deny(missing_docs)]\nfn main() {}\n";
+        let (contents, outcome) = apply_header_str(module, LICENSE, FileType::Rust).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+        assert!(contents.starts_with("/*\n* MIT License\n"));
+        assert!(contents.ends_with("*/\n//! Crate docs.\n#![deny(missing_docs)]\nfn main() {}\n"));
+
+        let (header, _) = apply_header_str("", LICENSE, FileType::Rust).unwrap();
+        let after_docs = "//! Crate docs.\n\n".to_owned() + &header + "fn main() {}\n";
+        let (contents, outcome) = apply_header_str(&after_docs, LICENSE, FileType::Rust).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+        assert_eq!(contents, after_docs);
+    }
+
+    #[test]
+    fn apply_header_str_perl_pod() {
+        let script = "use strict;\nprint \"hi\\n\";\n";
+        let (contents, outcome) = apply_header_str(script, LICENSE, FileType::Perl).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+        assert!(contents.starts_with("=pod\n\nMIT License\n"));
+        assert!(contents.contains("\n\n=cut\n\nuse strict;\n"));
+        let (pod, _) = contents.split_once("=cut").unwrap();
+        assert!(pod.lines().skip(1).all(|l| !l.starts_with('=')));
+        assert_runs_alike("perl", &["{}"], "a.pl", script, &contents);
+        let (_, outcome) = apply_header_str(&contents, LICENSE, FileType::Perl).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+        assert_eq!(strip_header_str(&contents, FileType::Perl).0, script);
+
+        let shebang = "#!/usr/bin/perl\n".to_owned() + script;
+        let (contents, _) = apply_header_str(&shebang, LICENSE, FileType::Perl).unwrap();
+        assert!(contents.starts_with("#!/usr/bin/perl\n\n=pod\n\n"));
+        assert_runs_alike("perl", &["{}"], "a.pl", &shebang, &contents);
+        let (_, outcome) = apply_header_str(&contents, LICENSE, FileType::Perl).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+    }
+
+    #[test]
+    fn apply_header_str_ruby_begin() {
+        let script = "# frozen_string_literal: true\nputs 'hi'\n";
+        let (contents, outcome) = apply_header_str(script, LICENSE, FileType::Ruby).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+        assert!(contents.starts_with("# frozen_string_literal: true\n=begin\nMIT License\n"));
+        assert!(contents.ends_with("\n=end\nputs 'hi'\n"));
+        assert_runs_alike("ruby", &["{}"], "a.rb", script, &contents);
+        let (_, outcome) = apply_header_str(&contents, LICENSE, FileType::Ruby).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+        assert_eq!(strip_header_str(&contents, FileType::Ruby).0, script);
+    }
+
+    #[test]
+    fn apply_header_str_vim_comments() {
+        let script = "let g:x = 1\n";
+        let (contents, outcome) = apply_header_str(script, LICENSE, FileType::Vim).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+        let (header, code) = contents.split_once("\n\n").unwrap();
+        assert!(header.lines().all(|l| l == "\"" || l.starts_with("\" ")));
+        assert_eq!(code, script);
+        let vim = [
+            "-es", "-u", "NONE", "-i", "NONE", "-N", "-S", "{}", "-c", "qa!",
+        ];
+        assert_runs_alike("vim", &vim, "a.vim", script, &contents);
+        let (_, outcome) = apply_header_str(&contents, LICENSE, FileType::Vim).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+        assert_eq!(strip_header_str(&contents, FileType::Vim).0, script);
+    }
+
+    #[test]
+    fn apply_header_str_markup() {
+        let svg = "<?xml version=\"1.0\"?>\n<svg/>\n";
+        let (contents, outcome) = apply_header_str(svg, LICENSE, FileType::XML).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+        assert!(contents.starts_with("<?xml version=\"1.0\"?>\n<!--\nMIT License\n"));
+        assert!(contents.ends_with("-->\n<svg/>\n"));
+        let (_, outcome) = apply_header_str(&contents, LICENSE, FileType::XML).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+
+        let html = "<!DOCTYPE html><html></html>\n";
+        let (contents, _) = apply_header_str(html, LICENSE, FileType::HTML).unwrap();
+        assert!(contents.starts_with("<!DOCTYPE html>\n<!--\n"));
+        assert!(contents.ends_with("-->\n<html></html>\n"));
+    }
+
+    #[test]
+    fn apply_header_str_updated() {
+        let old = "/*\n* Old License\n*\n* File created: 2023-10-02\n* Last updated: 2023-10-02\n*/\nfn main() {}\n";
+        let (contents, outcome) = apply_header_str(old, LICENSE, FileType::Rust).unwrap();
+        assert_eq!(outcome, Outcome::Updated);
+        assert!(contents.contains("* File created: 2023-10-02\n"));
+        assert!(!contents.contains("Old License"));
+        assert!(contents.ends_with("*/\nfn main() {}\n"));
+    }
+
+    #[test]
+    fn apply_header_str_protects_foreign() {
+        let gpl = "/*\n* ".to_owned()
+            + &License::from_spdx("GPL-3.0-or-later")
+                .unwrap()
+                .text()
+                .replace('\n', "\n* ")
+            + "\n*/\nint x;\n";
+        let (contents, outcome) = apply_header_str(&gpl, LICENSE, FileType::C).unwrap();
+        assert_eq!(outcome, Outcome::Skipped(Skip::ForeignHeader));
+        assert_eq!(contents, gpl);
+        assert_eq!(
+            check_header_str(&gpl, LICENSE, FileType::C).unwrap(),
+            outcome
+        );
+
+        let (contents, outcome) =
+            apply_header_str_with_fields(&gpl, LICENSE, FileType::C, Style::Block, &[], true)
+                .unwrap();
+        assert_eq!(outcome, Outcome::Updated);
+        assert!(contents.contains("MIT License"));
+    }
+
+    #[test]
+    fn check_header_str_missing() {
+        let outcome = check_header_str("def main(): pass\n", LICENSE, FileType::Python).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+    }
+
+    #[test]
+    fn check_header_str_up_to_date() {
+        let (contents, _) = apply_header_str("fn main() {}\n", LICENSE, FileType::Rust).unwrap();
+        let outcome = check_header_str(&contents, LICENSE, FileType::Rust).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+    }
+
+    #[test]
+    fn strip_header_str_removed() {
+        let (contents, _) = apply_header_str("fn main() {}\n", LICENSE, FileType::Rust).unwrap();
+        let (stripped, outcome) = strip_header_str(&contents, FileType::Rust);
+        assert_eq!(outcome, Outcome::Removed);
+        assert_eq!(stripped, "fn main() {}\n");
+
+        let (_, outcome) = strip_header_str(&stripped, FileType::Rust);
+        assert_eq!(outcome, Outcome::Unchanged);
+    }
+
+    #[test]
+    fn strip_header_str_keeps_foreign_comment() {
+        let contents = "/*\n* Copyright (c) 2023 Someone Else\n*/\n\nfn main() {}\n";
+        let (stripped, outcome) = strip_header_str(contents, FileType::Rust);
+        assert_eq!(outcome, Outcome::Unchanged);
+        assert_eq!(stripped, contents);
+    }
+
+    #[test]
+    fn strip_header_str_collapses_blank_lines() {
+        let (contents, _) =
+            apply_header_str("\n\nfn main() {}\n", LICENSE, FileType::Rust).unwrap();
+        let (stripped, _) = strip_header_str(&contents, FileType::Rust);
+        assert_eq!(stripped, "fn main() {}\n");
+    }
+
+    #[test]
+    fn relicense_str_mit_to_apache() {
+        let mit = License::resolve(Path::new("LICENSE")).unwrap();
+        let apache = License::from_spdx("Apache-2.0").unwrap();
+        let old = "/*\n* ".to_owned()
+            + &mit.text().trim_end().replace('\n', "\n* ")
+            + "\n* File created: 2023-10-02\n* Last updated: 2023-10-04\n*/\nfn main() {}\n";
+
+        let (contents, outcome) = relicense_str(&old, FileType::Rust, &mit, &apache).unwrap();
+        assert_eq!(outcome, Outcome::Updated);
+        assert!(contents.starts_with("/*\n* Copyright (c) 2023 Wilhelm Ågren\n*\n* Licensed"));
+        assert!(contents.contains("* File created: 2023-10-02\n"));
+        assert!(contents.ends_with("*/\nfn main() {}\n"));
+
+        let (_, outcome) = relicense_str(&contents, FileType::Rust, &mit, &apache).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+    }
+
+    #[test]
+    fn relicensed_file_checks_unchanged() {
+        let mit = License::resolve(Path::new("LICENSE")).unwrap();
+        let apache = License::from_spdx("Apache-2.0").unwrap();
+        let (old, _) = apply_header_str("fn main() {}\n", mit.text(), FileType::Rust).unwrap();
+        let (relicensed, _) = relicense_str(&old, FileType::Rust, &mit, &apache).unwrap();
+        assert!(relicensed.contains("* Copyright (c) 2023 Wilhelm Ågren\n"));
+
+        let outcome = check_header_str(&relicensed, apache.text(), FileType::Rust).unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+        let (updated, _) = apply_header_str(&relicensed, apache.text(), FileType::Rust).unwrap();
+        assert_eq!(updated, relicensed);
+
+        let e = apply_header_str("fn main() {}\n", apache.text(), FileType::Rust).unwrap_err();
+        assert!(e
+            .to_string()
+            .contains("`Copyright [yyyy] [name of copyright owner]`"));
+    }
+
+    #[test]
+    fn relicense_str_keeps_fields() {
+        let mit = License::resolve(Path::new("LICENSE")).unwrap();
+        let apache = License::from_spdx("Apache-2.0").unwrap();
+        let fields = vec![
+            (
+                "Author".to_owned(),
+                "Jane Doe <jane at example dot com>".to_owned(),
+            ),
+            ("Maintainers".to_owned(), "@a, @b".to_owned()),
+        ];
+        let (old, _) =
+            apply_header_str_with_fields("", mit.text(), FileType::C, Style::Block, &fields, false)
+                .unwrap();
+        assert!(old.contains("\n* Author: Jane Doe <jane at example dot com>\n"));
+
+        let (contents, outcome) = relicense_str(&old, FileType::C, &mit, &apache).unwrap();
+        assert_eq!(outcome, Outcome::Updated);
+        assert!(contents.contains("Licensed under the Apache License"));
+        assert!(contents.contains("\n* Author: Jane Doe <jane at example dot com>\n"));
+        assert!(contents.contains("\n* Maintainers: @a, @b\n* File created: "));
+    }
+
+    #[test]
+    fn relicense_str_unmatched() {
+        let mit = License::from_spdx("MIT").unwrap();
+        let apache = License::from_spdx("Apache-2.0").unwrap();
+        let gpl = "/*\n* ".to_owned()
+            + &License::from_spdx("GPL-3.0-or-later")
+                .unwrap()
+                .text()
+                .replace('\n', "\n* ")
+            + "\n*/\nint main() {}\n";
+        let (contents, outcome) = relicense_str(&gpl, FileType::C, &mit, &apache).unwrap();
+        assert_eq!(outcome, Outcome::Skipped(Skip::Unmatched));
+        assert_eq!(contents, gpl);
+
+        let (_, outcome) = relicense_str("int main() {}\n", FileType::C, &mit, &apache).unwrap();
+        assert_eq!(outcome, Outcome::Skipped(Skip::Unmatched));
+    }
+
+    #[test]
+    fn match_header_str_same_and_foreign() {
+        let mit = License::resolve(Path::new("LICENSE")).unwrap();
+        let body = mit.text().lines().skip(3).collect::<Vec<&str>>().join(" ");
+        let rewrapped = "/*\n * Copyright (c) 2021 Wilhelm Ågren\n * ".to_owned()
+            + &body
+            + "\n */\nfn main() {}\n";
+        assert!(matches!(
+            match_header_str(&rewrapped, &mit, FileType::Rust),
+            HeaderMatch::Same { .. }
+        ));
+        let vendored = rewrapped.replace("2021 Wilhelm Ågren", "2019 ACME Corp");
+        assert!(matches!(
+            match_header_str(&vendored, &mit, FileType::Rust),
+            HeaderMatch::Foreign { license: Some(l), .. } if l == "MIT"
+        ));
+
+        let bsd = "/*\n* ".to_owned()
+            + &License::from_spdx("BSD-3-Clause")
+                .unwrap()
+                .text()
+                .replace('\n', "\n* ")
+            + "\n*/\nint x;\n";
+        match match_header_str(&bsd, &mit, FileType::C) {
+            HeaderMatch::Foreign { score, license } => {
+                assert!(score < license::MATCH_THRESHOLD);
+                assert_eq!(license.as_deref(), Some("BSD-3-Clause"));
+            }
+            m => panic!("expected foreign header, got {:?}", m),
+        };
+
+        assert_eq!(
+            match_header_str("int x;\n", &mit, FileType::C),
+            HeaderMatch::Missing
+        );
+    }
+
+    #[test]
+    fn may_replace_foreign_allow() {
+        let options = Options::default().with_allow("vendor/**").unwrap();
+        assert!(options.may_replace_foreign(Path::new("src/vendor/lib.c")));
+        assert!(options.may_replace_foreign(Path::new("vendor/lib.c")));
+        assert!(!options.may_replace_foreign(Path::new("src/lib.c")));
+        assert!(Options::default()
+            .with_force(true)
+            .may_replace_foreign(Path::new("src/lib.c")));
+    }
+
+    #[test]
+    fn apply_header_protects_foreign() {
+        let dir = std::env::temp_dir().join(tmp::random_file_name());
+        let path = dir.join("gpl.c");
+        let gpl = "/*\n* ".to_owned()
+            + &License::from_spdx("GPL-3.0-or-later")
+                .unwrap()
+                .text()
+                .replace('\n', "\n* ")
+            + "\n*/\nint x;\n";
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, &gpl).unwrap();
+
+        let options = Options::default();
+        let outcome = apply_header(&path, &options).unwrap();
+        assert_eq!(outcome, Outcome::Skipped(Skip::ForeignHeader));
+        assert_eq!(fs::read_to_string(&path).unwrap(), gpl);
+        assert_eq!(check_header(&path, &options).unwrap(), outcome);
+
+        let outcome = apply_header(&path, &options.with_force(true)).unwrap();
+        assert_eq!(outcome, Outcome::Updated);
+        assert!(fs::read_to_string(&path).unwrap().contains("MIT License"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn apply_header_keeps_third_party_holder() {
+        let dir = std::env::temp_dir().join(tmp::random_file_name());
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("vendor.c");
+        let mit = License::from_spdx("MIT").unwrap();
+        let vendored = "/*\n * ".to_owned()
+            + &mit
+                .text()
+                .replace("<year> <copyright holders>", "2019 ACME Corp")
+                .trim_end()
+                .replace('\n', "\n * ")
+            + "\n */\nint x;\n";
+        fs::write(&path, &vendored).unwrap();
+
+        let options = Options::new("LICENSE");
+        let outcome = apply_header(&path, &options).unwrap();
+        assert_eq!(outcome, Outcome::Skipped(Skip::ForeignHeader));
+        assert_eq!(fs::read_to_string(&path).unwrap(), vendored);
+        assert_eq!(check_header(&path, &options).unwrap(), outcome);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn check_contents_ok() {
+        let options = Options::new("LICENSE");
+        let path = Path::new("staged/main.rs");
+        let outcome = check_contents(path, b"fn main() {}\n".to_vec(), &options).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+        let binary = check_contents(path, b"\x00\x01".to_vec(), &options).unwrap();
+        assert_eq!(binary, Outcome::Skipped(Skip::Binary));
+        let latin1 = check_contents(path, b"// \xe5\n".to_vec(), &options);
+        assert!(matches!(latin1, Err(Error::Decode { .. })));
+    }
+
+    #[test]
+    fn format_contents_ok() {
+        let options = Options::new("LICENSE");
+        let path = Path::new("does/not/exist.rs");
+        let (formatted, outcome) =
+            format_contents(path, b"fn main() {}\n".to_vec(), &options).unwrap();
+        assert_eq!(outcome, Outcome::Added);
+        assert!(!path.exists());
+        let formatted = String::from_utf8(formatted).unwrap();
+        assert!(formatted.starts_with("/*\n* MIT License"));
+        assert!(formatted.ends_with("*/\nfn main() {}\n"));
+
+        let again = format_contents(path, formatted.clone().into_bytes(), &options).unwrap();
+        assert_eq!(again, (formatted.into_bytes(), Outcome::Unchanged));
+
+        let script = "#!/usr/bin/env python3\nprint()\n";
+        let (formatted, _) = format_contents(Path::new("deploy"), script.into(), &options).unwrap();
+        let license = options.read_license().unwrap();
+        let (expected, _) = apply_header_str(script, license.text(), FileType::Python).unwrap();
+        assert_eq!(formatted, expected.into_bytes());
+
+        let binary = format_contents(path, b"\x00\x01".to_vec(), &options).unwrap();
+        assert_eq!(
+            binary,
+            (b"\x00\x01".to_vec(), Outcome::Skipped(Skip::Binary))
+        );
+    }
+
+    #[test]
+    fn apply_header_skips_binary() {
+        let dir = std::env::temp_dir().join(tmp::random_file_name());
+        fs::create_dir_all(&dir).unwrap();
+        let pyc = dir.join("main.pyc");
+        let py = dir.join("data.py");
+        let bytes = b"\x55\x0d\x0d\x0a\x00\x00\x00\x00print";
+        fs::write(&pyc, bytes).unwrap();
+        fs::write(&py, bytes).unwrap();
+
+        let options = Options::default();
+        for path in [&pyc, &py] {
+            let outcome = apply_header(path, &options).unwrap();
+            assert_eq!(outcome, Outcome::Skipped(Skip::Binary));
+            assert_eq!(fs::read(path).unwrap(), bytes);
+            assert_eq!(check_header(path, &options).unwrap(), outcome);
+            assert_eq!(strip_header(path, &options).unwrap(), outcome);
+            assert_eq!(classify(path, &options).unwrap().kind, HeaderKind::Binary);
+        }
+        assert!(apply_header(&dir.join("new.pyc"), &options).is_ok());
+        assert!(!dir.join("new.pyc").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn apply_header_disabled_by_config() {
+        let dir = std::env::temp_dir().join(tmp::random_file_name());
+        fs::create_dir_all(dir.join("vendor")).unwrap();
+        fs::write(dir.join("vendor").join(CONFIG_FILE), "skip = true\n").unwrap();
+        let path = dir.join("vendor/lib.rs");
+        fs::write(&path, "fn lib() {}\n").unwrap();
+
+        let options = Options::new("LICENSE").with_overrides(Some(Overrides::new(&dir).unwrap()));
+        let outcome = apply_header(&path, &options).unwrap();
+        assert_eq!(outcome, Outcome::Skipped(Skip::Disabled));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fn lib() {}\n");
+        assert_eq!(check_header(&path, &options).unwrap(), outcome);
+        let (contents, _) = format_contents(&path, b"fn x() {}\n".to_vec(), &options).unwrap();
+        assert_eq!(contents, b"fn x() {}\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn apply_header_unsupported_language() {
+        let dir = std::env::temp_dir().join(tmp::random_file_name());
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("scanner.l");
+        let lex = "%%\n[0-9]+ { return NUMBER; }\n%%\n";
+        fs::write(&path, lex).unwrap();
+
+        let options = Options::default();
+        let outcome = apply_header(&path, &options).unwrap();
+        assert_eq!(outcome, Outcome::Skipped(Skip::Unsupported));
+        assert_eq!(fs::read_to_string(&path).unwrap(), lex);
+        assert_eq!(check_header(&path, &options).unwrap(), outcome);
+        assert_eq!(strip_header(&path, &options).unwrap(), outcome);
+        let (contents, _) = format_contents(&path, lex.as_bytes().to_vec(), &options).unwrap();
+        assert_eq!(contents, lex.as_bytes());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn check_header_unsupported() {
+        let result = check_header(Path::new("LICENSE"), &Options::default());
+        assert!(matches!(result, Err(Error::UnsupportedFileType { .. })));
+    }
+}
@@ -0,0 +1,489 @@
+/*
+* MIT License
+*
+* Copyright (c) 2023 Wilhelm Ågren
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*
+* File created: 2026-10-18
+* Last updated: 2026-10-18
+*/
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{header, CommentStyle, Error, Result};
+
+/// The year or holder of a copyright line in a license template, e.g. `<year>` or
+/// `[name of copyright owner]`.
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)[<\[](year|yyyy|[a-z ]*\b(holders?|owners?|authors?)\b[a-z ]*)[>\]]")
+        .expect("could not compile regex")
+});
+
+/// Built-in license texts, as they are written in a file header, by SPDX id.
+static KNOWN: &[(&str, &str)] = &[
+    ("Apache-2.0", include_str!("licenses/Apache-2.0.txt")),
+    ("BSD-2-Clause", include_str!("licenses/BSD-2-Clause.txt")),
+    ("BSD-3-Clause", include_str!("licenses/BSD-3-Clause.txt")),
+    (
+        "GPL-3.0-or-later",
+        include_str!("licenses/GPL-3.0-or-later.txt"),
+    ),
+    ("ISC", include_str!("licenses/ISC.txt")),
+    ("MIT", include_str!("licenses/MIT.txt")),
+    ("MPL-2.0", include_str!("licenses/MPL-2.0.txt")),
+];
+
+/// The text of a license, and its SPDX id if it is one of the built-in licenses.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct License {
+    spdx: Option<String>,
+    text: String,
+}
+
+impl License {
+    /// Create a license from its text, recognising its SPDX id if it is a built-in one.
+    pub fn from_text(text: &str) -> Self {
+        let spdx = identify(text).map(|(id, _)| id.to_owned());
+
+        Self {
+            spdx,
+            text: text.to_owned(),
+        }
+    }
+
+    /// The built-in license with SPDX id `id`, ignoring case.
+    pub fn from_spdx(id: &str) -> Option<Self> {
+        KNOWN
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(id))
+            .map(|(known, text)| Self {
+                spdx: Some(known.to_string()),
+                text: text.to_string(),
+            })
+    }
+
+    /// Read the license from the file at `arg`, or if no such file exists, use the
+    /// built-in license with `arg` as SPDX id.
+    pub fn resolve(arg: &Path) -> Result<Self> {
+        match fs::read_to_string(arg) {
+            Ok(text) => Ok(Self::from_text(&text)),
+            Err(e) => match Self::from_spdx(&arg.to_string_lossy()) {
+                Some(l) => Ok(l),
+                None => Err(Error::LicenseNotFound {
+                    path: arg.to_path_buf(),
+                    source: e,
+                }),
+            },
+        }
+    }
+
+    /// The SPDX id of the license, if it is a built-in one.
+    pub fn spdx(&self) -> Option<&str> {
+        self.spdx.as_deref()
+    }
+
+    /// The full text of the license.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// How similar the docstring `header`, commented with `cs`, is to this license.
+    pub fn score(&self, header: &str, cs: &CommentStyle) -> f64 {
+        similarity(&header::uncomment(header, cs), &self.text)
+    }
+
+    /// Whether or not the docstring `header`, commented with `cs`, declares this
+    /// license, either through an `SPDX-License-Identifier` tag or through text
+    /// similar enough to the license text.
+    pub fn matches(&self, header: &str, cs: &CommentStyle) -> bool {
+        let text = header::uncomment(header, cs);
+        if let (Some(id), Some(tag)) = (self.spdx(), spdx_tag(&text)) {
+            return id.eq_ignore_ascii_case(&tag);
+        }
+
+        similarity(&text, &self.text) >= MATCH_THRESHOLD
+    }
+
+    /// The license text with its copyright lines replaced by `holders`, or with
+    /// `holders` put on top if the license has no copyright lines.
+    pub fn with_holders(&self, holders: &[String]) -> String {
+        replace_holders(&self.text, holders)
+    }
+
+    /// The first copyright line of the license that is a placeholder still to be
+    /// filled in, e.g. `Copyright [yyyy] [name of copyright owner]`, if any.
+    pub fn placeholder(&self) -> Option<&str> {
+        placeholder(&self.text)
+    }
+}
+
+/// The license `text` with its copyright lines replaced by `holders`, or with
+/// `holders` put on top if it has no copyright lines.
+fn replace_holders(text: &str, holders: &[String]) -> String {
+    if holders.is_empty() {
+        return text.to_owned();
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut replaced = false;
+    for line in text.split('\n') {
+        if !is_copyright(line) {
+            lines.push(line.to_owned());
+        } else if !replaced {
+            lines.extend(holders.iter().cloned());
+            replaced = true;
+        }
+    }
+
+    if !replaced {
+        let mut with_holders = holders.to_vec();
+        with_holders.push(String::new());
+        with_holders.extend(lines);
+        lines = with_holders;
+    }
+
+    lines.join("\n")
+}
+
+/// The first copyright line of the license `text` that is a placeholder, e.g.
+/// `Copyright (c) <year> <copyright holders>`, if any.
+fn placeholder(text: &str) -> Option<&str> {
+    text.lines()
+        .find(|l| is_copyright(l) && PLACEHOLDER.is_match(l))
+        .map(str::trim)
+}
+
+/// The license `text` with its placeholder copyright lines filled in with the
+/// copyright lines `holders`, e.g. those of the docstring it replaces. Errors if
+/// there is a placeholder but no holders to fill it in with.
+pub fn fill_placeholders<'a>(text: &'a str, holders: &[String]) -> Result<Cow<'a, str>> {
+    match (placeholder(text), holders.is_empty()) {
+        (None, _) => Ok(Cow::Borrowed(text)),
+        (Some(line), true) => Err(Error::Placeholder {
+            path: PathBuf::new(),
+            line: line.to_owned(),
+        }),
+        (Some(_), false) => Ok(Cow::Owned(replace_holders(text, holders))),
+    }
+}
+
+/// Whether or not `line` is a copyright line, e.g. `Copyright (c) 2023 Wilhelm Ågren`.
+fn is_copyright(line: &str) -> bool {
+    let line = line.trim_start().to_lowercase();
+    match line.strip_prefix("copyright") {
+        Some(rest) => {
+            let rest = rest.trim_start();
+            rest.starts_with("(c)")
+                || rest.starts_with('©')
+                || rest.starts_with('[')
+                || rest.starts_with('<')
+                || rest.starts_with(|c: char| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// All copyright lines in `text`, trimmed, leaving out placeholders.
+pub fn copyright_holders(text: &str) -> Vec<String> {
+    text.lines()
+        .filter(|l| is_copyright(l) && !PLACEHOLDER.is_match(l))
+        .map(|l| l.trim().to_owned())
+        .collect()
+}
+
+/// Whether or not the copyright lines `a` and `b` name the same holders, ignoring
+/// years, case, punctuation and `All rights reserved`.
+pub fn same_holders(a: &[String], b: &[String]) -> bool {
+    let names = |lines: &[String]| -> HashSet<String> {
+        lines
+            .iter()
+            .map(|l| {
+                let l: String = l
+                    .chars()
+                    .map(|c| match c.is_alphanumeric() {
+                        true => c.to_ascii_lowercase(),
+                        false => ' ',
+                    })
+                    .collect();
+                l.split_whitespace()
+                    .filter(|w| {
+                        !matches!(*w, "copyright" | "c" | "all" | "rights" | "reserved")
+                            && !w.chars().all(|c| c.is_ascii_digit())
+                    })
+                    .collect::<Vec<&str>>()
+                    .join(" ")
+            })
+            .collect()
+    };
+
+    names(a) == names(b)
+}
+
+/// The license id of a `SPDX-License-Identifier: <id>` tag in `text`, if any.
+pub fn spdx_tag(text: &str) -> Option<String> {
+    text.lines().find_map(|l| {
+        l.split_once("SPDX-License-Identifier:")
+            .map(|(_, id)| id.trim().to_owned())
+    })
+}
+
+/// Whether or not `text` looks like it declares a license, i.e. it is similar to a
+/// built-in license, has an SPDX tag or mentions a copyright or license.
+pub fn looks_like_license(text: &str) -> bool {
+    if spdx_tag(text).is_some() || identify(text).is_some() {
+        return true;
+    }
+
+    normalise(text)
+        .iter()
+        .any(|w| matches!(w.as_str(), "copyright" | "license" | "licensed" | "licence"))
+}
+
+/// Similarity at or above which two license texts are considered the same license.
+pub static MATCH_THRESHOLD: f64 = 0.95;
+
+/// The words of a license text normalised for comparison. Case, punctuation,
+/// whitespace and line wrapping are ignored, years are masked and copyright lines
+/// are reduced to a single word so that differing holders still compare equal.
+/// Docstring dates and SPDX tags are dropped.
+fn normalise(text: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for line in text.lines() {
+        if line.contains("File created: ")
+            || line.contains("Last updated: ")
+            || line.contains("SPDX-License-Identifier:")
+        {
+            continue;
+        }
+        if is_copyright(line) {
+            words.push("copyright".to_owned());
+            continue;
+        }
+
+        let line: String = line
+            .chars()
+            .map(|c| match c.is_alphanumeric() {
+                true => c.to_ascii_lowercase(),
+                false => ' ',
+            })
+            .collect();
+        for word in line.split_whitespace() {
+            match word.len() == 4 && word.chars().all(|c| c.is_ascii_digit()) {
+                true => words.push("<year>".to_owned()),
+                false => words.push(word.to_owned()),
+            };
+        }
+    }
+
+    words
+}
+
+/// How similar two license texts are, from 0 to 1, after normalising them. Computed
+/// as the Dice coefficient of their word pairs, so that reordered or missing
+/// sentences lower the score gradually.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let pairs = |text: &str| -> HashSet<String> {
+        let words = normalise(text);
+        match words.len() {
+            0 => HashSet::new(),
+            1 => words.into_iter().collect(),
+            _ => words.windows(2).map(|w| w.join(" ")).collect(),
+        }
+    };
+
+    let (a, b) = (pairs(a), pairs(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    let shared = a.intersection(&b).count();
+    (2 * shared) as f64 / (a.len() + b.len()) as f64
+}
+
+/// The SPDX id of the built-in license most similar to `text` and its similarity,
+/// if any is similar enough to be considered the same license.
+pub fn identify(text: &str) -> Option<(&'static str, f64)> {
+    KNOWN
+        .iter()
+        .map(|(id, known)| (*id, similarity(text, known)))
+        .filter(|(_, score)| *score >= MATCH_THRESHOLD)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+#[cfg(test)]
+mod tests_license {
+    use super::*;
+
+    #[test]
+    fn from_text_recognises_spdx() {
+        let text = std::fs::read_to_string("LICENSE").unwrap();
+        assert_eq!(License::from_text(&text).spdx(), Some("MIT"));
+        assert_eq!(License::from_text("lol").spdx(), None);
+    }
+
+    #[test]
+    fn resolve_file_or_spdx() {
+        assert_eq!(
+            License::resolve(Path::new("LICENSE")).unwrap().spdx(),
+            Some("MIT")
+        );
+        let apache = License::resolve(Path::new("apache-2.0")).unwrap();
+        assert_eq!(apache.spdx(), Some("Apache-2.0"));
+        assert!(matches!(
+            License::resolve(Path::new("lol")),
+            Err(Error::LicenseNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn matches_header() {
+        let cs = CommentStyle::new("/*", "* ", "*/");
+        let mit = License::from_spdx("MIT").unwrap();
+        let header = "/*\n* MIT License\n*\n* Copyright (c) 2023 Wilhelm Ågren\n*\n".to_owned()
+            + &MIT_BODY.replace('\n', "\n* ")
+            + "\n* File created: 2023-10-02\n*/\n";
+        assert!(mit.matches(&header, &cs));
+        assert!(!License::from_spdx("ISC").unwrap().matches(&header, &cs));
+
+        let tagged = "/*\n* SPDX-License-Identifier: MIT\n*/\n";
+        assert!(mit.matches(tagged, &cs));
+    }
+
+    static MIT_BODY: &str =
+        "Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the \"Software\"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED \"AS IS\", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.";
+
+    #[test]
+    fn similarity_ignores_wrapping_and_holders() {
+        let mit = License::from_spdx("MIT").unwrap();
+        let rewrapped = "Copyright 2019-2021 ACME Corp. All rights reserved.\n\n".to_owned()
+            + &MIT_BODY.split_whitespace().collect::<Vec<&str>>().join(" ");
+        assert!(similarity(&rewrapped, mit.text()) >= MATCH_THRESHOLD);
+        assert_eq!(identify(&rewrapped).map(|(id, _)| id), Some("MIT"));
+    }
+
+    #[test]
+    fn similarity_tells_similar_licenses_apart() {
+        let bsd2 = License::from_spdx("BSD-2-Clause").unwrap();
+        let bsd3 = License::from_spdx("BSD-3-Clause").unwrap();
+        assert!(similarity(bsd2.text(), bsd3.text()) < MATCH_THRESHOLD);
+        assert_eq!(
+            identify(bsd3.text()).map(|(id, _)| id),
+            Some("BSD-3-Clause")
+        );
+        assert!(similarity(bsd2.text(), MIT_BODY) < 0.5);
+        assert_eq!(identify("Some helper functions."), None);
+    }
+
+    #[test]
+    fn score_commented_header() {
+        let cs = CommentStyle::new("#=", "= ", "=#");
+        let isc = License::from_spdx("ISC").unwrap();
+        let header = "#=\n= ".to_owned()
+            + &isc
+                .text()
+                .replace("<year> <copyright holders>", "2023 Wilhelm")
+                .replace('\n', "\n= ")
+            + "\n=#\n";
+        assert!(isc.score(&header, &cs) >= MATCH_THRESHOLD);
+        assert!(isc.matches(&header, &cs));
+    }
+
+    #[test]
+    fn looks_like_license_ok() {
+        assert!(looks_like_license("Copyright 2023 ACME"));
+        assert!(looks_like_license("Licensed under the GPL."));
+        assert!(looks_like_license("SPDX-License-Identifier: MIT"));
+        assert!(!looks_like_license(
+            "Helpers for parsing command line arguments."
+        ));
+    }
+
+    #[test]
+    fn with_holders_replaces_copyright() {
+        let apache = License::from_spdx("Apache-2.0").unwrap();
+        let holders = vec!["Copyright (c) 2023 Wilhelm Ågren".to_owned()];
+        let text = apache.with_holders(&holders);
+        assert!(text.starts_with("Copyright (c) 2023 Wilhelm Ågren\n\nLicensed under"));
+
+        let mpl = License::from_spdx("MPL-2.0").unwrap();
+        let text = mpl.with_holders(&holders);
+        assert!(text.starts_with("Copyright (c) 2023 Wilhelm Ågren\n\nThis Source Code Form"));
+    }
+
+    #[test]
+    fn fill_placeholders_ok() {
+        let apache = License::from_spdx("Apache-2.0").unwrap();
+        assert_eq!(
+            apache.placeholder(),
+            Some("Copyright [yyyy] [name of copyright owner]")
+        );
+        let holders = vec!["Copyright 2023 ACME".to_owned()];
+        let filled = fill_placeholders(apache.text(), &holders).unwrap();
+        assert!(filled.starts_with("Copyright 2023 ACME\n"));
+        let e = fill_placeholders(apache.text(), &[]).unwrap_err();
+        assert!(matches!(e, Error::Placeholder { .. }));
+
+        let own = "Copyright (c) 2023 Jane <jane@example.com>\nAll rights reserved.";
+        assert_eq!(placeholder(own), None);
+        assert_eq!(fill_placeholders(own, &[]).unwrap(), own);
+        assert!(copyright_holders(License::from_spdx("MIT").unwrap().text()).is_empty());
+    }
+
+    #[test]
+    fn same_holders_ignores_years() {
+        let ours = vec!["Copyright (c) 2023 Wilhelm Ågren".to_owned()];
+        let again = vec!["copyright 2019-2024 Wilhelm Ågren. All rights reserved.".to_owned()];
+        let acme = vec!["Copyright (c) 2019 ACME Corp".to_owned()];
+        assert!(same_holders(&ours, &again));
+        assert!(!same_holders(&ours, &acme));
+    }
+
+    #[test]
+    fn copyright_holders_ok() {
+        let text = "MIT License\n\nCopyright (c) 2023 Wilhelm Ågren\ncopyright 2024 Someone\n\
+            copyright notice and this permission notice appear in all copies.\n";
+        assert_eq!(
+            copyright_holders(text),
+            vec!["Copyright (c) 2023 Wilhelm Ågren", "copyright 2024 Someone"]
+        );
+    }
+}
@@ -22,329 +22,558 @@
 * SOFTWARE.
 *
 * File created: 2023-09-30
-* Last updated: 2023-10-04
+* Last updated: 2026-10-18
 */
 
+use std::collections::HashSet;
 use std::fs;
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
-use std::{io, io::Write};
+use std::process::ExitCode;
+use std::time::Duration;
 
 use log::{error, info, warn};
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 
-use glob::glob;
+use docstring_rs::{
+    apply_header, changed_files, check_contents, check_header, classify, collect_files,
+    format_contents, install_hook, parse_file_list, relicense, stage, staged_contents,
+    staged_files, strip_header, unstaged_files, Audit, Config, Error, FileType, Filter, License,
+    Options, Outcome, Skip, Watch, HOOK_MARKER,
+};
 
 mod args;
-mod comment;
-mod docstring;
-mod filetype;
-mod tmp;
-
-use args::Args;
-use comment::CommentStyle;
-use docstring::Docstring;
-use filetype::FileType;
-use tmp::tmp_file_from_path;
-
-fn remove_docstring_from_contents(c: Vec<u8>, cs: CommentStyle) -> Result<String, io::Error> {
-    let c: String = match String::from_utf8(c) {
-        Ok(c) => c,
-        Err(_) => {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Could not create String from Vec<u8>",
-            ))
+
+use args::{
+    AddArgs, Args, Command, FormatArgs, GuardArgs, InstallHookArgs, PreCommitArgs, RelicenseArgs,
+    RemoveArgs, StyleArgs, UpdateArgs, WalkArgs, WatchArgs,
+};
+
+/// Create a new file with a docstring, or add it to the file if it already exists.
+fn add(args: AddArgs, config: &Config) -> Result<(), Error> {
+    let (d, f) = args.paths();
+    let directory = Path::new(&d);
+    let file_name = Path::new(&f);
+
+    let path_builder: PathBuf = directory.join(file_name);
+    let target_path = Path::new(&path_builder);
+
+    let filetype: FileType = match config.languages.resolve(target_path) {
+        Ok(f) => f,
+        Err(Error::UnsupportedLanguage { .. }) => {
+            let outcome = Outcome::Skipped(Skip::Unsupported);
+            println!("{} `{}`", outcome, &target_path.display());
+            return Ok(());
+        }
+        Err(e) => {
+            error!(
+                "Could not find a filetype in the filename: `{}` due to `{}`",
+                &file_name.display(),
+                e
+            );
+            return Err(e);
         }
     };
 
-    let start = cs.start();
-    let end = cs.end();
-
-    let mut has_started: bool = false;
-    let mut ignore_span_start: usize = 0;
-    let mut ignore_span_end: usize = 0;
-    let mut num_chars = 0;
-    for line in c.split('\n') {
-        if !has_started {
-            if line.starts_with(start) {
-                has_started = true;
-                ignore_span_start = num_chars;
-            }
-        } else if line.starts_with(end) {
-            ignore_span_end = num_chars + line.len() + 1;
-            break;
+    if target_path.exists() {
+        warn!("Target file already exists, will prepend to top of file...");
+    }
+
+    let options = licensed(config, &args.license).with_file_type(filetype);
+    let options = guarded(options, &args.guard)?;
+    let options = styled(options, &args.style)?;
+    match apply_header(target_path, &options) {
+        Ok(outcome) => println!("{} `{}`", outcome, &target_path.display()),
+        Err(e) => {
+            error!(
+                "Could not add docstring to the file `{}` due to `{}`",
+                &target_path.display(),
+                e
+            );
+            return Err(e);
         }
-        num_chars += line.len() + 1;
+    };
+
+    Ok(())
+}
+
+/// Find all files to process, prompting for the language first if interactive.
+/// With `--since`, only the files changed in the git repository are processed.
+fn walk_files(
+    args: &mut WalkArgs,
+    interactive: bool,
+    config: &Config,
+) -> Result<Vec<PathBuf>, Error> {
+    let mut filter = walk_filter(args, interactive, config)?;
+    if let Some(since) = &args.since {
+        filter = filter.with_only(&changed_files(Path::new("."), since, args.untracked)?);
     }
+    collect_files(&walk_paths(args)?, &filter)
+}
 
-    let potential_start = &c[0..ignore_span_start];
-    let part = &c[ignore_span_end..];
-    let keep = potential_start.to_owned() + part;
+/// The files and directories given by the `--directory`, positional and
+/// `--files-from` arguments.
+fn walk_paths(args: &WalkArgs) -> Result<Vec<PathBuf>, Error> {
+    let mut paths: Vec<PathBuf> = args.directory.iter().map(PathBuf::from).collect();
+    paths.extend(args.paths.iter().map(PathBuf::from));
+    if let Some(list) = &args.files_from {
+        let read = match list.as_str() {
+            "-" => {
+                let mut buf = Vec::new();
+                io::stdin().read_to_end(&mut buf).map(|_| buf)
+            }
+            path => fs::read(path),
+        };
+        let read = read.map_err(|e| Error::Read {
+            path: PathBuf::from(list),
+            source: e,
+        })?;
+        paths.extend(parse_file_list(&read));
+    }
 
-    Ok(keep)
+    Ok(paths)
 }
 
-///
-fn update_existing_file(data: &[u8], path: &Path, ft: FileType) -> Result<(), io::Error> {
-    let tmp_path: PathBuf = tmp_file_from_path(path);
-    match fs::write(&tmp_path, data) {
-        Ok(_) => info!(
-            "Wrote docstring contents to tmp file: `{}`",
-            &tmp_path.display()
-        ),
-        Err(e) => return Err(e),
-    };
+/// The filter selecting the files to process, prompting for the language first if
+/// interactive.
+fn walk_filter(args: &mut WalkArgs, interactive: bool, config: &Config) -> Result<Filter, Error> {
+    if interactive && args.lang.is_none() {
+        args.get_lang_from_user();
+    }
 
-    let contents: Vec<u8> = match fs::read(path) {
-        Ok(c) => {
-            info!("Read contents of `{}` successfully", &path.display());
-            c
-        }
-        Err(e) => return Err(e),
+    let filetype = match &args.lang {
+        Some(l) => Some(FileType::try_from_name(l)?),
+        None => None,
     };
+    let mut filter = Filter::default()
+        .with_file_type(filetype)
+        .with_languages(config.languages.clone())
+        .with_overrides(config.overrides.clone());
+    for pattern in &args.ignore {
+        filter = filter.with_ignore(pattern)?;
+    }
 
-    let c_string = match remove_docstring_from_contents(contents, ft.get_comment_style()) {
-        Ok(c) => c,
-        Err(e) => return Err(e),
-    };
+    Ok(filter)
+}
 
-    let contents = c_string.as_bytes();
+/// The options set by the config file, with the LICENSE file given by `--license`
+/// taking precedence over it and over the config files in subdirectories.
+fn licensed(config: &Config, license: &Option<String>) -> Options {
+    let options = config.apply(Options::default());
+    match license {
+        Some(l) => options.with_license(l),
+        None => options,
+    }
+}
 
-    let mut tmp = match fs::OpenOptions::new().append(true).open(&tmp_path) {
-        Ok(f) => {
-            info!("Opened `{}` with append mode", &tmp_path.display());
-            f
-        }
-        Err(e) => return Err(e),
-    };
+/// Apply the `--force` and `--allow` arguments to `options`.
+fn guarded(mut options: Options, guard: &GuardArgs) -> Result<Options, Error> {
+    options = options.with_force(guard.force);
+    for pattern in &guard.allow {
+        options = options.with_allow(pattern)?;
+    }
 
-    match tmp.write_all(contents) {
-        Ok(_) => info!(
-            "Updated contents from `{}` to `{}`",
-            &path.display(),
-            &tmp_path.display()
-        ),
-        Err(e) => return Err(e),
-    };
+    Ok(options)
+}
 
-    match fs::copy(&tmp_path, path) {
-        Ok(_) => info!(
-            "Copied contents from `{}` to `{}`",
-            &tmp_path.display(),
-            &path.display()
-        ),
-        Err(e) => return Err(e),
-    };
+/// Apply the `--style` arguments to `options`.
+fn styled(mut options: Options, style: &StyleArgs) -> Result<Options, Error> {
+    for choice in &style.style {
+        options = match &choice.lang {
+            Some(lang) => options.with_style_for(FileType::try_from_name(lang)?, choice.style),
+            None => options.with_style(choice.style),
+        };
+    }
 
-    match fs::remove_file(&tmp_path) {
-        Ok(_) => info!("Removed the temporary file `{}`", &tmp_path.display()),
-        Err(e) => return Err(e),
-    };
+    Ok(options)
+}
+
+/// Add or update the docstring of every supported file in the directory.
+fn update_directory_recursively(
+    mut args: UpdateArgs,
+    interactive: bool,
+    config: &Config,
+) -> Result<(), Error> {
+    let options = guarded(licensed(config, &args.walk.license), &args.guard)?;
+    let options = styled(options, &args.style)?;
+    for target_path in walk_files(&mut args.walk, interactive, config)? {
+        match apply_header(&target_path, &options) {
+            Ok(outcome) => println!("{} `{}`", outcome, &target_path.display()),
+            Err(e) => {
+                error!(
+                    "Could not prepend docstring to the file `{}` due to `{}`",
+                    &target_path.display(),
+                    e
+                );
+                return Err(e);
+            }
+        };
+    }
 
-    info!("Updated docstring at: `{}`", &path.display());
     Ok(())
 }
 
-///
-fn add_to_new_file(data: &[u8], path: &Path) -> Result<(), io::Error> {
-    match fs::write(path, data) {
-        Ok(_) => {
-            info!("Wrote docstring contents to file: `{}`", &path.display());
-            Ok(())
-        }
-        Err(e) => Err(e),
+/// Add or update the docstring of the source code on stdin, writing the result to
+/// stdout.
+fn format_stdin(args: FormatArgs, config: &Config) -> Result<(), Error> {
+    let mut options = guarded(licensed(config, &args.license), &args.guard)?;
+    if let Some(lang) = &args.lang {
+        options = options.with_file_type(FileType::try_from_name(lang)?);
     }
+    let options = styled(options, &args.style)?;
+    let path = PathBuf::from(args.stdin_filename.as_deref().unwrap_or("<stdin>"));
+
+    let mut contents = Vec::new();
+    io::stdin()
+        .read_to_end(&mut contents)
+        .map_err(|e| Error::Read {
+            path: PathBuf::from("<stdin>"),
+            source: e,
+        })?;
+    let (formatted, outcome) = format_contents(&path, contents, &options)?;
+    info!("{} `{}`", outcome, path.display());
+    io::stdout()
+        .write_all(&formatted)
+        .map_err(|e| Error::Write {
+            path: PathBuf::from("<stdout>"),
+            source: e,
+        })
 }
 
-///
-fn create_directory(dir: &Path) -> Result<(), io::Error> {
-    let mut pathbuf = PathBuf::new();
-    for component in dir.components() {
-        pathbuf.push(component);
-        if pathbuf.as_path().exists() {
-            info!("Path `{}` already exists", &pathbuf.display());
-            continue;
+/// Add docstrings to the files that are created in the directory until interrupted.
+fn watch_directory(mut args: WatchArgs, interactive: bool, config: &Config) -> Result<(), Error> {
+    let options = guarded(licensed(config, &args.walk.license), &args.guard)?;
+    let options = styled(options, &args.style)?;
+    if args.walk.since.is_some() {
+        return Err(Error::Watch {
+            path: PathBuf::from("."),
+            reason: "`--since` cannot be combined with watching".to_owned(),
+        });
+    }
+    let filter = walk_filter(&mut args.walk, interactive, config)?;
+    let dir = match walk_paths(&args.walk)?.as_slice() {
+        [dir] if dir.is_dir() => dir.clone(),
+        paths => {
+            return Err(Error::Watch {
+                path: paths.first().cloned().unwrap_or_default(),
+                reason: "exactly one directory can be watched".to_owned(),
+            })
         }
-        match fs::create_dir(&pathbuf) {
-            Ok(_) => info!("Creating path `{}`", &pathbuf.display()),
-            Err(e) => return Err(e),
-        };
+    };
+    let watch = Watch::new(&dir, filter, options)?;
+    println!("Watching `{}` for new files...", dir.display());
+    watch.run(
+        Duration::from_millis(args.debounce),
+        |path, result| match result {
+            Ok(outcome) => println!("{} `{}`", outcome, path.display()),
+            Err(e) => error!(
+                "Could not add docstring to the file `{}` due to `{}`",
+                path.display(),
+                e
+            ),
+        },
+    )
+}
+
+/// Check the docstring of every supported file in the directory, returns whether
+/// or not all of them are up to date.
+fn check_directory_recursively(
+    mut args: UpdateArgs,
+    interactive: bool,
+    config: &Config,
+) -> Result<bool, Error> {
+    let options = guarded(licensed(config, &args.walk.license), &args.guard)?;
+    let options = styled(options, &args.style)?;
+    let mut up_to_date = true;
+    for target_path in walk_files(&mut args.walk, interactive, config)? {
+        let outcome = check_header(&target_path, &options)?;
+        up_to_date &= report_check(&target_path, outcome);
     }
 
-    Ok(())
+    Ok(up_to_date)
 }
 
-fn update_directory_recursively(mut args: Args) -> Result<(), io::Error> {
-    if &args.file_name == "*.*" {
-        args.get_filetype_from_user();
+/// Print the outcome of checking the docstring of `path`, returns whether or not
+/// it is up to date or left alone on purpose.
+fn report_check(path: &Path, outcome: Outcome) -> bool {
+    match outcome {
+        Outcome::Unchanged => {
+            info!("Docstring is up to date in `{}`", path.display());
+            true
+        }
+        // files that are left alone on purpose, e.g. with a foreign header, cannot
+        // be fixed and so do not fail the check
+        Outcome::Skipped(reason) => {
+            println!("Skipped ({}) `{}`", reason, path.display());
+            true
+        }
+        Outcome::Updated => {
+            println!("Outdated docstring in `{}`", path.display());
+            false
+        }
+        _ => {
+            println!("Missing docstring in `{}`", path.display());
+            false
+        }
+    }
+}
+
+/// Check or fix the docstrings of the files about to be committed, the staged files
+/// unless paths are given. Returns whether or not the commit may go ahead: all
+/// docstrings were up to date, or were fixed and staged.
+fn pre_commit(args: PreCommitArgs, config: &Config) -> Result<bool, Error> {
+    let options = guarded(licensed(config, &args.license), &args.guard)?;
+    let options = styled(options, &args.style)?;
+    let mut filter = Filter::default()
+        .with_languages(config.languages.clone())
+        .with_overrides(config.overrides.clone());
+    for pattern in &args.ignore {
+        filter = filter.with_ignore(pattern)?;
+    }
+
+    let staged = args.paths.is_empty();
+    let paths: Vec<PathBuf> = match staged {
+        true => staged_files(Path::new("."))?
+            .into_iter()
+            .filter(|p| p.is_file())
+            .collect(),
+        false => args.paths.iter().map(PathBuf::from).collect(),
     };
+    let paths = collect_files(&paths, &filter)?;
 
-    let dir_start = Path::new(&args.directory);
-    let filetype = match FileType::try_from_filename(&args.file_name) {
-        Ok(ft) => ft,
-        Err(e) => return Err(e),
+    // Files with unstaged changes are committed as staged, so that is what is checked.
+    // Fixing them would mean staging changes that were left out of the commit.
+    let unstaged = match staged {
+        true => unstaged_files(Path::new("."), &paths)?,
+        false => HashSet::new(),
     };
-    let license = Path::new(&args.license);
-
-    for file_ending in filetype.file_endings() {
-        let files = match glob(format!("./{}/**/*.{}", &dir_start.display(), file_ending).as_str())
-        {
-            Ok(f) => f,
-            Err(_) => {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "Could not glob directory and file type",
-                ))
-            }
-        };
-        for file in files {
-            let target_path = match file {
-                Ok(f) => f,
-                Err(_) => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        "Could not glob directory and file type",
-                    ))
-                }
-            };
-            let mut docstring =
-                Docstring::new(target_path.to_path_buf(), license.to_path_buf(), filetype);
-            match docstring.try_read_license() {
-                Ok(_) => (),
-                Err(e) => return Err(e),
-            };
-            match docstring.try_find_created_date() {
-                Ok(_) => (),
-                Err(e) => return Err(e),
-            };
-            match docstring.format_contents() {
-                Ok(_) => (),
-                Err(e) => return Err(e),
-            };
-            let contents = docstring.get_formatted_contents().unwrap();
-            match update_existing_file(contents.as_bytes(), Path::new(&target_path), filetype) {
-                Ok(_) => (),
-                Err(e) => {
-                    error!(
-                        "Could not prepend docstring to the file `{}` due to `{:?}`",
-                        &target_path.display(),
-                        e
+    let mut ok = true;
+    let mut fixed = Vec::new();
+    for path in paths {
+        if unstaged.contains(&path) {
+            let contents = staged_contents(Path::new("."), &path)?;
+            if !report_check(&path, check_contents(&path, contents, &options)?) {
+                ok = false;
+                if args.fix {
+                    println!(
+                        "  stage the other changes to `{}` to fix it",
+                        path.display()
                     );
-                    return Err(e);
                 }
-            };
-            println!("Updated `{}`", &target_path.display());
+            }
+        } else if !args.fix {
+            ok &= report_check(&path, check_header(&path, &options)?);
+        } else {
+            let outcome = apply_header(&path, &options)?;
+            println!("{} `{}`", outcome, path.display());
+            if matches!(outcome, Outcome::Added | Outcome::Updated) {
+                fixed.push(path);
+            }
         }
     }
 
-    Ok(())
+    match args.stage {
+        true => stage(Path::new("."), &fixed)?,
+        false => ok &= fixed.is_empty(),
+    };
+    Ok(ok)
 }
 
-fn main() -> Result<(), io::Error> {
-    env_logger::init();
+/// Write a git pre-commit hook running `docstring-rs pre-commit`.
+fn install_pre_commit_hook(args: InstallHookArgs) -> Result<(), Error> {
+    let mut command = "exec docstring-rs pre-commit".to_owned();
+    if let Some(license) = &args.license {
+        let license = license.replace('\'', "'\\''");
+        command.push_str(&format!(" --license '{license}'"));
+    }
+    if args.fix {
+        command.push_str(" --fix");
+    }
+    if args.stage {
+        command.push_str(" --stage");
+    }
+    let script = format!("#!/bin/sh\n{HOOK_MARKER}\n{command}\n");
 
-    let args = Args::parse();
+    let path = install_hook(Path::new("."), &script, args.overwrite)?;
+    println!("Installed the pre-commit hook `{}`", path.display());
+    Ok(())
+}
 
-    if args.update() {
-        match update_directory_recursively(args) {
-            Ok(_) => return Ok(()),
-            Err(e) => return Err(e),
-        }
-    } else {
-        let args = Args::try_from_user();
+/// Remove the generated docstring of every supported file in the directory.
+fn remove_directory_recursively(
+    mut args: RemoveArgs,
+    interactive: bool,
+    config: &Config,
+) -> Result<(), Error> {
+    let options = licensed(config, &args.walk.license).with_dry_run(args.dry_run);
+    for target_path in walk_files(&mut args.walk, interactive, config)? {
+        match strip_header(&target_path, &options)? {
+            Outcome::Removed if args.dry_run => {
+                println!("Would remove `{}`", &target_path.display())
+            }
+            Outcome::Removed => println!("Removed `{}`", &target_path.display()),
+            Outcome::Skipped(reason) => {
+                println!("Skipped ({}) `{}`", reason, &target_path.display())
+            }
+            _ => info!("No docstring to remove in `{}`", &target_path.display()),
+        };
+    }
 
-        let (d, f, l) = args.paths();
-        let directory = Path::new(&d);
-        let file_name = Path::new(&f);
-        let license = Path::new(&l);
+    Ok(())
+}
 
-        let filetype: FileType = match FileType::try_from_filename(&args.file_name) {
-            Ok(f) => f,
-            Err(e) => {
-                error!(
-                    "Could not find a filetype in the filename: `{}` due to `{}`",
-                    &file_name.display(),
-                    e
-                );
-                return Err(e);
+/// Relicense every supported file in the directory, reporting the files whose
+/// docstring matches neither license.
+fn relicense_directory_recursively(
+    mut args: RelicenseArgs,
+    interactive: bool,
+    config: &Config,
+) -> Result<(), Error> {
+    let from = License::resolve(Path::new(&args.from))?;
+    let options = licensed(config, &args.walk.license).with_dry_run(args.dry_run);
+    let mut unmatched: Vec<PathBuf> = Vec::new();
+    for target_path in walk_files(&mut args.walk, interactive, config)? {
+        match relicense(&target_path, &options, &from)? {
+            Outcome::Updated if args.dry_run => {
+                println!("Would relicense `{}`", &target_path.display())
             }
+            Outcome::Updated => println!("Relicensed `{}`", &target_path.display()),
+            Outcome::Skipped(Skip::Unmatched) => unmatched.push(target_path),
+            Outcome::Skipped(reason) => {
+                println!("Skipped ({}) `{}`", reason, &target_path.display())
+            }
+            _ => info!("Already relicensed `{}`", &target_path.display()),
         };
+    }
 
-        let path_builder: PathBuf = directory.join(file_name);
-        let target_path = Path::new(&path_builder);
+    if !unmatched.is_empty() {
+        println!(
+            "{} file(s) have a docstring matching neither license:",
+            unmatched.len()
+        );
+        for path in unmatched {
+            println!("  {}", path.display());
+        }
+    }
 
-        if !directory.exists() {
-            info!(
-                "Directory `{}` does not already exist, creating it...",
-                &directory.display()
-            );
+    Ok(())
+}
 
-            match create_directory(directory) {
-                Ok(()) => info!("Successfully created directory!"),
-                Err(e) => {
-                    error!(
-                        "Could not create directory `{}` due to `{:?}`",
-                        &directory.display(),
-                        e
-                    );
-                    return Err(e);
-                }
-            };
-        }
+/// Audit the license declared by every supported file in the directory, returns
+/// whether or not all of them agree with the project license.
+fn audit_directory_recursively(
+    mut args: WalkArgs,
+    interactive: bool,
+    config: &Config,
+) -> Result<bool, Error> {
+    let options = licensed(config, &args.license);
+    let project = License::resolve(&options.license)?;
+    let mut audit = Audit::default();
+    for target_path in walk_files(&mut args, interactive, config)? {
+        audit.add(&target_path, classify(&target_path, &options)?);
+    }
+
+    println!("Licenses:");
+    for (license, count) in audit.by_license() {
+        println!("  {:<24} {}", license, count);
+    }
+
+    println!("\nDirectories:");
+    for (dir, counts) in audit.by_directory() {
+        let counts: Vec<String> = counts
+            .iter()
+            .map(|(license, count)| format!("{}: {}", license, count))
+            .collect();
+        println!("  {:<24} {}", dir.display().to_string(), counts.join(", "));
+    }
 
-        let mut docstring =
-            Docstring::new(target_path.to_path_buf(), license.to_path_buf(), filetype);
-        match docstring.try_read_license() {
-            Ok(_) => (),
-            Err(e) => return Err(e),
+    let disagreeing = audit.disagreeing();
+    if disagreeing.is_empty() {
+        return Ok(true);
+    }
+
+    println!(
+        "\nFiles disagreeing with the project license ({}):",
+        project
+            .spdx()
+            .unwrap_or(&options.license.display().to_string())
+    );
+    for (path, declaration) in disagreeing {
+        println!(
+            "  {} declares {} ({})",
+            path.display(),
+            declaration.license_name(),
+            declaration.kind
+        );
+    }
+
+    Ok(false)
+}
+
+/// Print every supported language, including those declared in the config file,
+/// together with its comment style, file endings and file names.
+fn list_languages() {
+    for filetype in FileType::registry() {
+        let cs = filetype.get_comment_style();
+        let comment = format!("{} {}", cs.start(), cs.end());
+        let comment = match cs.is_line() {
+            true => cs.normal().trim_end(),
+            false => comment.trim(),
         };
+        let names = filetype
+            .file_endings()
+            .iter()
+            .map(|e| format!(".{e}"))
+            .chain(filetype.file_names().iter().map(|n| n.to_string()))
+            .collect::<Vec<String>>();
+        println!(
+            "{:<12} {:<12} {}",
+            filetype.to_string(),
+            comment,
+            names.join(", ")
+        );
+    }
+}
 
-        if docstring.target_exists() {
-            warn!("Target file already exists, will prepend to top of file...");
-            match docstring.try_find_created_date() {
-                Ok(_) => (),
-                Err(e) => return Err(e),
-            };
-            match docstring.format_contents() {
-                Ok(_) => (),
-                Err(e) => return Err(e),
-            };
-
-            let contents = docstring.get_formatted_contents().unwrap();
-            match update_existing_file(contents.as_bytes(), target_path, filetype) {
-                Ok(_) => (),
-                Err(e) => {
-                    error!(
-                        "Could not prepend docstring to the file `{}` due to `{:?}`",
-                        &target_path.display(),
-                        e
-                    );
-                    return Err(e);
-                }
-            };
-        } else {
-            match docstring.format_contents() {
-                Ok(_) => (),
-                Err(e) => return Err(e),
-            };
-
-            let contents = docstring.get_formatted_contents().unwrap();
-            match add_to_new_file(contents.as_bytes(), target_path) {
-                Ok(_) => (),
-                Err(e) => {
-                    error!(
-                        "Could not add docstring to the file `{}` due to `{:?}`",
-                        &target_path.display(),
-                        e
-                    );
-                    return Err(e);
-                }
-            }
+fn run(args: Args) -> Result<bool, Error> {
+    let interactive = args.interactive;
+    let config = Config::resolve(args.config.as_deref().map(Path::new))?;
+    match args.command {
+        Some(Command::Add(a)) => add(a, &config)?,
+        Some(Command::Update(w)) => update_directory_recursively(w, interactive, &config)?,
+        Some(Command::Check(w)) => return check_directory_recursively(w, interactive, &config),
+        Some(Command::Remove(r)) => remove_directory_recursively(r, interactive, &config)?,
+        Some(Command::Relicense(r)) => relicense_directory_recursively(r, interactive, &config)?,
+        Some(Command::Audit(w)) => return audit_directory_recursively(w, interactive, &config),
+        Some(Command::Format(f)) => format_stdin(f, &config)?,
+        Some(Command::Watch(w)) => watch_directory(w, interactive, &config)?,
+        Some(Command::PreCommit(p)) => return pre_commit(p, &config),
+        Some(Command::InstallHook(h)) => install_pre_commit_hook(h)?,
+        Some(Command::ListLanguages) => list_languages(),
+        None if interactive => add(AddArgs::try_from_user(), &config)?,
+        None => {
+            let _ = Args::command().print_help();
+            return Ok(false);
         }
     };
 
     info!("⚡Successfully created/updated docstrings!⚡");
 
-    Ok(())
+    Ok(true)
+}
+
+fn main() -> ExitCode {
+    env_logger::init();
+
+    let args = Args::parse();
+
+    match run(args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{}", e.diagnostic());
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -22,7 +22,7 @@
 * SOFTWARE.
 *
 * File created: 2023-10-02
-* Last updated: 2023-10-04
+* Last updated: 2026-10-18
 */
 
 use std::path::{Path, PathBuf};
@@ -33,7 +33,7 @@ static FILENAMELEN: usize = 16;
 
 /// Generate a pseudo-random string from alphanumerica characters of length
 /// FILENAMELEN.
-fn random_file_name() -> String {
+pub fn random_file_name() -> String {
     Alphanumeric.sample_string(&mut rand::thread_rng(), FILENAMELEN)
 }
 
@@ -52,6 +52,6 @@ mod tests_tmp {
 
     #[test]
     fn tmp_file_from() {
-        let _tp: PathBuf = tmp_file_from_path(Path::new("src".into()));
+        let _tp: PathBuf = tmp_file_from_path(Path::new("src"));
     }
 }
@@ -0,0 +1,328 @@
+/*
+* MIT License
+*
+* Copyright (c) 2023 Wilhelm Ågren
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*
+* File created: 2026-10-18
+* Last updated: 2026-10-18
+*/
+
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use glob::{glob_with, MatchOptions, Pattern};
+
+use crate::{Error, FileType, LanguageMap, Overrides, Result};
+
+/// Decides which files found while walking a directory should be processed.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// Only keep files of this `FileType`, otherwise keep all supported files.
+    pub file_type: Option<FileType>,
+    /// Skip files whose path, relative to the walked directory, matches any of these.
+    pub ignore: Vec<Pattern>,
+    /// Globs pinning files to a `FileType`, consulted before the file name.
+    pub languages: LanguageMap,
+    /// Only keep these files, canonicalized, e.g. the files changed in a branch.
+    /// Otherwise keep files regardless of their path.
+    pub only: Option<HashSet<PathBuf>>,
+    /// The config files in subdirectories, which may disable processing the files
+    /// below them.
+    pub overrides: Option<Overrides>,
+}
+
+impl Filter {
+    /// Only keep files of `file_type`.
+    pub fn with_file_type(mut self, file_type: Option<FileType>) -> Self {
+        self.file_type = file_type;
+        self
+    }
+
+    /// Pin the files matched by `languages` to their `FileType`.
+    pub fn with_languages(mut self, languages: LanguageMap) -> Self {
+        self.languages = languages;
+        self
+    }
+
+    /// Skip the files below config files in `overrides` that disable processing them.
+    pub fn with_overrides(mut self, overrides: Option<Overrides>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// Only keep the files among `paths`. Paths that do not exist are dropped.
+    pub fn with_only(mut self, paths: &[PathBuf]) -> Self {
+        let only = paths.iter().filter_map(|p| p.canonicalize().ok()).collect();
+        self.only = Some(only);
+        self
+    }
+
+    /// Skip files matching the glob `pattern`, e.g. `vendor/**` or `*_pb2.py`.
+    pub fn with_ignore(mut self, pattern: &str) -> Result<Self> {
+        match Pattern::new(pattern) {
+            Ok(p) => self.ignore.push(p),
+            Err(e) => {
+                return Err(Error::Glob {
+                    pattern: pattern.to_owned(),
+                    reason: e.to_string(),
+                })
+            }
+        };
+        Ok(self)
+    }
+
+    /// Whether or not `path`, relative to the walked directory, is ignored.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.ignore.iter().any(|p| {
+            p.matches_path(path)
+                || path
+                    .ancestors()
+                    .skip(1)
+                    .any(|a| !a.as_os_str().is_empty() && p.matches_path(a))
+        })
+    }
+
+    /// The `FileType` of `path` if the file should be processed. Binary artifacts
+    /// built from a kept `FileType`, e.g. `.pyc` files, are kept as well, so that
+    /// they can be reported as skipped.
+    pub fn keep(&self, path: &Path) -> Option<FileType> {
+        if let Some(only) = &self.only {
+            if !only.contains(&path.canonicalize().ok()?) {
+                return None;
+            }
+        }
+        // a broken config file is reported once the file is processed
+        if let Some(overrides) = &self.overrides {
+            if overrides.skips(path).unwrap_or(false) {
+                return None;
+            }
+        }
+        let found = match self.languages.resolve(path) {
+            Ok(ft) => ft,
+            Err(_) => FileType::artifact_of(&path.to_string_lossy())?,
+        };
+        match self.file_type {
+            Some(wanted) if wanted != found => None,
+            _ => Some(found),
+        }
+    }
+}
+
+/// Whether or not `pattern` matches `path` or any of its trailing components, so
+/// that `vendor/**` matches `src/vendor/lib.c` and `*.pl` matches `bin/run.pl`.
+pub(crate) fn matches_tail(pattern: &Pattern, path: &Path) -> bool {
+    let components: Vec<_> = path.components().collect();
+    (0..components.len()).any(|i| {
+        let tail: PathBuf = components[i..].iter().collect();
+        pattern.matches_path(&tail)
+    })
+}
+
+/// Recursively find all files in `dir` that are kept by `filter`. Hidden files and
+/// directories are skipped.
+pub fn find_files(dir: &Path, filter: &Filter) -> Result<Vec<PathBuf>> {
+    let pattern = dir.join("**").join("*");
+    let pattern = pattern.to_string_lossy();
+    let options = MatchOptions {
+        require_literal_leading_dot: true,
+        ..Default::default()
+    };
+
+    let files = match glob_with(&pattern, options) {
+        Ok(f) => f,
+        Err(e) => {
+            return Err(Error::Glob {
+                pattern: pattern.into_owned(),
+                reason: e.to_string(),
+            })
+        }
+    };
+
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for file in files {
+        let path = match file {
+            Ok(f) => f,
+            Err(e) => {
+                return Err(Error::Glob {
+                    pattern: pattern.into_owned(),
+                    reason: e.to_string(),
+                })
+            }
+        };
+        if !path.is_file() {
+            continue;
+        }
+        let relative = path.strip_prefix(dir).unwrap_or(&path);
+        if filter.is_ignored(relative) {
+            continue;
+        }
+        if filter.keep(&path).is_some() {
+            paths.push(path);
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Find the files to process among `paths`, which may mix files and directories.
+/// Directories are searched recursively as by `find_files`, while files are kept
+/// if `filter` keeps them. Every file is listed once, in the order given.
+pub fn collect_files(paths: &[PathBuf], filter: &Filter) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    for path in paths {
+        let found = if path.is_dir() {
+            find_files(path, filter)?
+        } else if path.is_file() {
+            match !filter.is_ignored(path) && filter.keep(path).is_some() {
+                true => vec![path.clone()],
+                false => Vec::new(),
+            }
+        } else {
+            return Err(Error::Read {
+                path: path.clone(),
+                source: io::Error::from(io::ErrorKind::NotFound),
+            });
+        };
+        files.extend(found.into_iter().filter(|f| seen.insert(f.clone())));
+    }
+
+    Ok(files)
+}
+
+/// Split a list of paths, e.g. the output of `git ls-files -z` or `fd`, separated
+/// by NUL bytes if there are any and by newlines otherwise. Empty entries are
+/// skipped.
+pub fn parse_file_list(list: &[u8]) -> Vec<PathBuf> {
+    let list = String::from_utf8_lossy(list);
+    let entries: Vec<&str> = match list.contains('\0') {
+        true => list.split('\0').collect(),
+        false => list.lines().collect(),
+    };
+    entries
+        .into_iter()
+        .filter(|e| !e.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests_walk {
+    use super::*;
+
+    #[test]
+    fn find_files_rust() {
+        let filter = Filter::default().with_file_type(Some(FileType::Rust));
+        let files = find_files(Path::new("src"), &filter).unwrap();
+        assert!(files.contains(&PathBuf::from("src/walk.rs")));
+        assert!(files.iter().all(|f| f.extension().unwrap() == "rs"));
+    }
+
+    #[test]
+    fn find_files_all_skips_unsupported() {
+        let files = find_files(Path::new("src"), &Filter::default()).unwrap();
+        assert!(files.contains(&PathBuf::from("src/main.rs")));
+        assert!(files
+            .iter()
+            .all(|f| FileType::try_from_filename(&f.to_string_lossy()).is_ok()));
+    }
+
+    #[test]
+    fn find_files_ignored() {
+        let filter = Filter::default().with_ignore("walk.rs").unwrap();
+        let files = find_files(Path::new("src"), &filter).unwrap();
+        assert!(!files.contains(&PathBuf::from("src/walk.rs")));
+        assert!(files.contains(&PathBuf::from("src/main.rs")));
+    }
+
+    #[test]
+    fn find_files_scripts() {
+        let dir = std::env::temp_dir().join(crate::tmp::random_file_name());
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        std::fs::write(dir.join("bin/deploy"), "#!/bin/sh\necho hi\n").unwrap();
+        std::fs::write(dir.join("README"), "Hello\n").unwrap();
+
+        let files = find_files(&dir, &Filter::default()).unwrap();
+        assert_eq!(files, vec![dir.join("bin/deploy")]);
+        let filter = Filter::default().with_file_type(Some(FileType::Python));
+        assert!(find_files(&dir, &filter).unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn collect_files_mixed() {
+        let paths = [
+            PathBuf::from("src/walk.rs"),
+            PathBuf::from("src"),
+            PathBuf::from("README.md"),
+            PathBuf::from("LICENSE"),
+        ];
+        let filter = Filter::default().with_file_type(Some(FileType::Rust));
+        let files = collect_files(&paths, &filter).unwrap();
+        assert_eq!(files[0], PathBuf::from("src/walk.rs"));
+        assert_eq!(files.iter().filter(|f| f.ends_with("walk.rs")).count(), 1);
+        assert!(files.contains(&PathBuf::from("src/main.rs")));
+        assert!(!files.contains(&PathBuf::from("README.md")));
+
+        let all = collect_files(&paths[2..], &Filter::default()).unwrap();
+        assert_eq!(all, vec![PathBuf::from("README.md")]);
+        let missing = collect_files(&[PathBuf::from("missing.rs")], &filter);
+        assert!(matches!(missing, Err(Error::Read { .. })));
+    }
+
+    #[test]
+    fn keep_only() {
+        let filter = Filter::default().with_only(&[
+            PathBuf::from("./src/../src/walk.rs"),
+            PathBuf::from("src/missing.rs"),
+        ]);
+        assert_eq!(filter.keep(Path::new("src/walk.rs")), Some(FileType::Rust));
+        assert_eq!(filter.keep(Path::new("src/lib.rs")), None);
+        let files = collect_files(&[PathBuf::from("src")], &filter).unwrap();
+        assert_eq!(files, vec![PathBuf::from("src/walk.rs")]);
+    }
+
+    #[test]
+    fn parse_file_list_separators() {
+        let expected = vec![PathBuf::from("a b.rs"), PathBuf::from("src/c.py")];
+        assert_eq!(parse_file_list(b"a b.rs\nsrc/c.py\n"), expected);
+        assert_eq!(parse_file_list(b"a b.rs\r\n\nsrc/c.py"), expected);
+        assert_eq!(parse_file_list(b"a b.rs\0src/c.py\0"), expected);
+        assert!(parse_file_list(b"").is_empty());
+    }
+
+    #[test]
+    fn keep_artifacts() {
+        let filter = Filter::default().with_file_type(Some(FileType::Python));
+        let pyc = Path::new("__pycache__/main.cpython-311.pyc");
+        assert_eq!(filter.keep(pyc), Some(FileType::Python));
+        let filter = Filter::default().with_file_type(Some(FileType::Rust));
+        assert_eq!(filter.keep(pyc), None);
+    }
+
+    #[test]
+    fn is_ignored_directory() {
+        let filter = Filter::default().with_ignore("vendor").unwrap();
+        assert!(filter.is_ignored(Path::new("vendor/lib/a.c")));
+        assert!(!filter.is_ignored(Path::new("src/vendor.c")));
+    }
+}
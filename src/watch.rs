@@ -0,0 +1,250 @@
+/*
+* MIT License
+*
+* Copyright (c) 2023 Wilhelm Ågren
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*
+* File created: 2026-10-18
+* Last updated: 2026-10-18
+*/
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::{apply_header, find_files, Error, Filter, Options, Outcome, Result};
+
+/// How long to wait after the last change to a file before giving it a docstring,
+/// so that the burst of writes an editor makes when saving is handled once.
+pub const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Collects changed paths until they have not changed for a while.
+#[derive(Debug)]
+pub struct Debouncer {
+    delay: Duration,
+    pending: HashMap<PathBuf, Instant>,
+}
+
+impl Debouncer {
+    /// Settle paths once they have not changed for `delay`.
+    pub fn new(delay: Duration) -> Self {
+        Debouncer {
+            delay,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Record that `path` changed at `now`, restarting its delay.
+    pub fn push(&mut self, path: PathBuf, now: Instant) {
+        self.pending.insert(path, now);
+    }
+
+    /// Take the paths that have settled by `now`, in sorted order.
+    pub fn ready(&mut self, now: Instant) -> Vec<PathBuf> {
+        let mut ready: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, changed)| now.duration_since(**changed) >= self.delay)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &ready {
+            self.pending.remove(path);
+        }
+        ready.sort();
+        ready
+    }
+
+    /// How long from `now` until the next path settles, if any are pending.
+    pub fn timeout(&self, now: Instant) -> Option<Duration> {
+        self.pending
+            .values()
+            .map(|changed| self.delay.saturating_sub(now.duration_since(*changed)))
+            .min()
+    }
+}
+
+/// Gives docstrings to the files that appear in a directory, and to files in it
+/// that become empty. Files that existed when watching started are otherwise left
+/// alone, even if they have no docstring.
+#[derive(Debug)]
+pub struct Watch {
+    dir: PathBuf,
+    filter: Filter,
+    options: Options,
+    known: HashSet<PathBuf>,
+}
+
+impl Watch {
+    /// Watch `dir` for new files kept by `filter`, applying docstrings with `options`.
+    pub fn new(dir: &Path, filter: Filter, options: Options) -> Result<Self> {
+        let known = find_files(dir, &filter)?.into_iter().collect();
+        Ok(Watch {
+            dir: dir.to_path_buf(),
+            filter,
+            options,
+            known,
+        })
+    }
+
+    /// Apply the docstring to the file at `path`, relative to the watched directory,
+    /// if it is a new file or an empty one. Returns `None` if the file is left alone
+    /// because it is gone, hidden, ignored, not supported or was already known.
+    pub fn stamp(&mut self, path: &Path) -> Option<Result<Outcome>> {
+        if !path.is_file() {
+            self.known.remove(path);
+            return None;
+        }
+        let relative = path.strip_prefix(&self.dir).unwrap_or(path);
+        if is_hidden(relative) || self.filter.is_ignored(relative) {
+            return None;
+        }
+        self.filter.keep(path)?;
+
+        let empty = fs::metadata(path).is_ok_and(|m| m.len() == 0);
+        if !self.known.insert(path.to_path_buf()) && !empty {
+            return None;
+        }
+        Some(apply_header(path, &self.options))
+    }
+
+    /// Block while watching the directory, waiting for changes to settle for
+    /// `debounce` and calling `report` with the outcome of every file that was
+    /// given a docstring.
+    pub fn run(
+        mut self,
+        debounce: Duration,
+        mut report: impl FnMut(&Path, Result<Outcome>),
+    ) -> Result<()> {
+        let watch_error = |e: notify::Error| Error::Watch {
+            path: self.dir.clone(),
+            reason: e.to_string(),
+        };
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+        watcher
+            .watch(&self.dir, RecursiveMode::Recursive)
+            .map_err(watch_error)?;
+        info!("Watching `{}` for new files", self.dir.display());
+
+        // Events carry absolute paths, while the known files are relative to the
+        // directory as given.
+        let root = fs::canonicalize(&self.dir).unwrap_or_else(|_| self.dir.clone());
+        let mut pending = Debouncer::new(debounce);
+        loop {
+            let received = match pending.timeout(Instant::now()) {
+                Some(timeout) => rx.recv_timeout(timeout),
+                None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(Ok(event)) => match event.kind {
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
+                        for path in event.paths {
+                            let path = match path.strip_prefix(&root) {
+                                Ok(relative) => self.dir.join(relative),
+                                Err(_) => path,
+                            };
+                            pending.push(path, Instant::now());
+                        }
+                    }
+                    _ => {}
+                },
+                Ok(Err(e)) => warn!("Could not watch `{}`: {}", self.dir.display(), e),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+
+            for path in pending.ready(Instant::now()) {
+                if let Some(result) = self.stamp(&path) {
+                    report(&path, result);
+                }
+            }
+        }
+    }
+}
+
+/// Whether or not any component of `path` is hidden, e.g. `.git/config`.
+fn is_hidden(path: &Path) -> bool {
+    path.components().any(|c| match c {
+        Component::Normal(name) => name.to_string_lossy().starts_with('.'),
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests_watch {
+    use super::*;
+
+    #[test]
+    fn debouncer_settles() {
+        let start = Instant::now();
+        let delay = Duration::from_millis(100);
+        let mut debouncer = Debouncer::new(delay);
+        assert_eq!(debouncer.timeout(start), None);
+
+        debouncer.push(PathBuf::from("b.rs"), start);
+        debouncer.push(PathBuf::from("a.rs"), start);
+        debouncer.push(PathBuf::from("b.rs"), start + delay / 2);
+        assert_eq!(debouncer.timeout(start), Some(delay));
+        assert!(debouncer.ready(start + delay / 2).is_empty());
+        assert_eq!(debouncer.ready(start + delay), vec![PathBuf::from("a.rs")]);
+        assert_eq!(debouncer.timeout(start + delay), Some(delay / 2));
+        assert_eq!(
+            debouncer.ready(start + delay * 2),
+            vec![PathBuf::from("b.rs")]
+        );
+        assert_eq!(debouncer.timeout(start), None);
+    }
+
+    #[test]
+    fn stamp_new_and_emptied() {
+        let dir = std::env::temp_dir().join(crate::tmp::random_file_name());
+        fs::create_dir_all(dir.join("vendor")).unwrap();
+        let old = dir.join("old.rs");
+        fs::write(&old, "fn old() {}\n").unwrap();
+
+        let filter = Filter::default().with_ignore("vendor").unwrap();
+        let options = Options::new("LICENSE");
+        let mut watch = Watch::new(&dir, filter, options).unwrap();
+
+        let new = dir.join("new.rs");
+        fs::write(&new, "fn new() {}\n").unwrap();
+        assert_eq!(watch.stamp(&new).unwrap().unwrap(), Outcome::Added);
+        assert!(watch.stamp(&new).is_none());
+
+        fs::write(&old, "fn old() { 1 }\n").unwrap();
+        assert!(watch.stamp(&old).is_none());
+        fs::write(&old, "").unwrap();
+        assert_eq!(watch.stamp(&old).unwrap().unwrap(), Outcome::Added);
+
+        for name in ["vendor/lib.rs", ".hidden.rs", "notes.txt"] {
+            fs::write(dir.join(name), "").unwrap();
+            assert!(watch.stamp(&dir.join(name)).is_none());
+        }
+        assert!(watch.stamp(&dir.join("gone.rs")).is_none());
+
+        assert!(fs::read_to_string(&new).unwrap().starts_with("/*"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}